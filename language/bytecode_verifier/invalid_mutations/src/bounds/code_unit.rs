@@ -213,7 +213,7 @@ impl<'a> ApplyCodeUnitBoundsContext<'a> {
                     | Or | And | Not | Eq | Neq | Lt | Gt | Le | Ge | Abort
                     | GetTxnGasUnitPrice | GetTxnMaxGasUnits | GetGasRemaining
                     | GetTxnSenderAddress | CreateAccount | EmitEvent | GetTxnSequenceNumber
-                    | GetTxnPublicKey => panic!(
+                    | GetTxnPublicKey | GetTxnExpirationTime => panic!(
                         "Bytecode has no internal index: {:?}",
                         interesting[bytecode_idx]
                     ),
@@ -260,6 +260,6 @@ fn is_interesting(bytecode: &Bytecode) -> bool {
         | Add | Sub | Mul | Mod | Div | BitOr | BitAnd | Xor | Or | And | Not | Eq | Neq | Lt
         | Gt | Le | Ge | Abort | GetTxnGasUnitPrice | GetTxnMaxGasUnits | GetGasRemaining
         | GetTxnSenderAddress | CreateAccount | EmitEvent | GetTxnSequenceNumber
-        | GetTxnPublicKey => false,
+        | GetTxnPublicKey | GetTxnExpirationTime => false,
     }
 }
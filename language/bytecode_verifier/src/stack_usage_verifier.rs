@@ -140,6 +140,7 @@ impl<'a> StackUsageVerifier<'a> {
             | Bytecode::GetGasRemaining
             | Bytecode::GetTxnPublicKey
             | Bytecode::GetTxnSequenceNumber
+            | Bytecode::GetTxnExpirationTime
             | Bytecode::GetTxnSenderAddress => 1,
             Bytecode::CreateAccount => -1,
             Bytecode::EmitEvent => -3,
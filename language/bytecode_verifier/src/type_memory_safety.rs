@@ -686,7 +686,8 @@ impl<'a> TypeAndMemorySafetyAnalysis<'a> {
             Bytecode::GetTxnGasUnitPrice
             | Bytecode::GetTxnMaxGasUnits
             | Bytecode::GetGasRemaining
-            | Bytecode::GetTxnSequenceNumber => {
+            | Bytecode::GetTxnSequenceNumber
+            | Bytecode::GetTxnExpirationTime => {
                 self.stack.push(StackAbstractValue {
                     signature: SignatureToken::U64,
                     value: AbstractValue::full_value(false),
@@ -2221,6 +2221,11 @@ impl<S: Scope + Sized> Compiler<S> {
                         function_frame.push()?;
                         Ok(self.make_singleton_vec_deque(InferredType::U64))
                     }
+                    Builtin::GetTxnExpirationTime => {
+                        code.code.push(Bytecode::GetTxnExpirationTime);
+                        function_frame.push()?;
+                        Ok(self.make_singleton_vec_deque(InferredType::U64))
+                    }
                     Builtin::GetTxnPublicKey => {
                         code.code.push(Bytecode::GetTxnPublicKey);
                         function_frame.push()?;
@@ -281,6 +281,8 @@ pub enum Builtin {
     GetTxnSender,
     /// Returns the sequence number of the current transaction.
     GetTxnSequenceNumber,
+    /// Returns the expiration time (in seconds) of the current transaction.
+    GetTxnExpirationTime,
     /// Returns the unit of gas remain to be used for now.
     GetGasRemaining,
     /// Emit an event
@@ -1162,6 +1164,7 @@ impl fmt::Display for Builtin {
             Builtin::GetTxnPublicKey => write!(f, "get_txn_public_key"),
             Builtin::GetTxnSender => write!(f, "get_txn_sender"),
             Builtin::GetTxnSequenceNumber => write!(f, "get_txn_sequence_number"),
+            Builtin::GetTxnExpirationTime => write!(f, "get_txn_expiration_time"),
             Builtin::GetGasRemaining => write!(f, "get_gas_remaining"),
             Builtin::MoveFrom(t) => write!(f, "move_from<{}>", t),
             Builtin::MoveToSender(t) => write!(f, "move_to_sender<{}>", t),
@@ -89,7 +89,8 @@ impl AUTransactionGen for P2PTransferGen {
                 // 6 means the balance was insufficient while trying to deduct gas costs in the
                 // epilogue.
                 // TODO: define these values in a central location
-                status = TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Aborted(6)));
+                status =
+                    TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Aborted(6, None)));
             }
             (true, false, _) => {
                 // Enough to pass validation but not to do the transfer. The transaction will be run
@@ -97,7 +98,9 @@ impl AUTransactionGen for P2PTransferGen {
                 sender.sequence_number += 1;
                 sender.balance -= *gas_costs::PEER_TO_PEER_TOO_LOW;
                 // 10 means the balance was insufficient while trying to transfer.
-                status = TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Aborted(10)));
+                status = TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Aborted(
+                    10, None,
+                )));
             }
             (false, _, _) => {
                 // Not enough gas to pass validation. Nothing will happen.
@@ -14,6 +14,10 @@ use crate::{
     executor::FakeExecutor,
 };
 use proptest::{collection::vec, prelude::*};
+use types::{
+    transaction::TransactionStatus,
+    vm_error::{ExecutionStatus, VMStatus},
+};
 
 proptest! {
     // These tests are pretty slow but quite comprehensive, so run a smaller number of them.
@@ -90,7 +94,7 @@ pub(crate) fn run_and_assert_gas_cost_stability(
 
     for (idx, (output, expected)) in outputs.iter().zip(&expected_statuses).enumerate() {
         prop_assert_eq!(
-            output.status(),
+            &without_abort_location(output.status()),
             expected,
             "unexpected status for transaction {}",
             idx
@@ -123,7 +127,7 @@ pub(crate) fn run_and_assert_universe(
 
     for (idx, (output, expected)) in outputs.iter().zip(&expected_statuses).enumerate() {
         prop_assert_eq!(
-            output.status(),
+            &without_abort_location(output.status()),
             expected,
             "unexpected status for transaction {}",
             idx
@@ -178,3 +182,16 @@ pub(crate) fn assert_accounts_match(
     }
     Ok(())
 }
+
+/// The universe doesn't model where in the code a transaction aborts, so statuses are compared
+/// without their abort locations.
+fn without_abort_location(status: &TransactionStatus) -> TransactionStatus {
+    match status {
+        TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Aborted(err_code, _))) => {
+            TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Aborted(
+                *err_code, None,
+            )))
+        }
+        status => status.clone(),
+    }
+}
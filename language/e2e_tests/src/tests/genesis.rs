@@ -6,12 +6,14 @@ use assert_matches::assert_matches;
 use crypto::signing::KeyPair;
 use types::{
     access_path::AccessPath,
+    account_address::AccountAddress,
     account_config,
     test_helpers::transaction_test_helpers,
-    transaction::TransactionStatus,
+    transaction::{Program, TransactionArgument, TransactionStatus},
     vm_error::{VMStatus, VMValidationStatus},
     write_set::{WriteOp, WriteSetMut},
 };
+use vm_genesis::{decode_stdlib_program, encode_transfer_program, StdlibScript};
 
 #[test]
 fn invalid_genesis_write_set() {
@@ -35,3 +37,26 @@ fn invalid_genesis_write_set() {
         VMStatus::Validation(VMValidationStatus::InvalidWriteSet)
     );
 }
+
+#[test]
+fn decode_stdlib_program_round_trip() {
+    let recipient = AccountAddress::random();
+    let program = encode_transfer_program(&recipient, 100);
+    assert_eq!(
+        decode_stdlib_program(&program),
+        Some((
+            StdlibScript::PeerToPeer,
+            vec![
+                TransactionArgument::Address(recipient),
+                TransactionArgument::U64(100),
+            ]
+        ))
+    );
+
+    // Neither other scripts nor programs publishing modules are stdlib programs.
+    let other = Program::new(vec![1, 2, 3], vec![], vec![]);
+    assert_eq!(decode_stdlib_program(&other), None);
+    let (code, args, _) = program.into_inner();
+    let with_module = Program::new(code, vec![vec![]], args);
+    assert_eq!(decode_stdlib_program(&with_module), None);
+}
@@ -249,10 +249,15 @@ pub fn test_publishing_with_error() {
     assert_eq!(executor.verify_transaction(txn2.clone()), None);
 
     let result = executor.execute_block(vec![txn1, txn2]);
-    assert_eq!(
-        result[0].status(),
-        &TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Aborted(42)))
-    );
+    match result[0].status() {
+        TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Aborted(
+            42,
+            Some(location),
+        ))) => {
+            assert_eq!(location.function, "main")
+        }
+        status => panic!("unexpected status {:?}", status),
+    }
 
     assert_eq!(
         result[1].status(),
@@ -187,11 +187,17 @@ fn zero_amount_peer_to_peer() {
     );
 
     let output = &executor.execute_block(vec![txn])[0];
-    // Error code 7 means that the transaction was a zero-amount one.
-    assert_eq!(
-        output.status(),
-        &TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Aborted(7))),
-    );
+    // Error code 7 means that the transaction was a zero-amount one, which is caught when
+    // depositing the coin.
+    match output.status() {
+        TransactionStatus::Keep(VMStatus::Execution(ExecutionStatus::Aborted(
+            7,
+            Some(location),
+        ))) => {
+            assert_eq!(location.function, "deposit")
+        }
+        status => panic!("unexpected status {:?}", status),
+    }
 }
 
 #[test]
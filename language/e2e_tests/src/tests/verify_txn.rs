@@ -15,6 +15,7 @@ use config::config::{NodeConfigHelpers, VMPublishingOption};
 use crypto::signing::KeyPair;
 use std::collections::HashSet;
 use tiny_keccak::Keccak;
+use transaction_builder::{transaction::script_code_hash, EncodeError};
 use types::{
     account_address::AccountAddress,
     test_helpers::transaction_test_helpers,
@@ -26,7 +27,7 @@ use types::{
     },
 };
 use vm::gas_schedule::{self, GasAlgebra};
-use vm_genesis::encode_transfer_program;
+use vm_genesis::{allowing_script_hashes, encode_allowlisted_script, encode_transfer_program};
 
 #[test]
 fn verify_signature() {
@@ -102,6 +103,34 @@ fn verify_whitelist() {
     )
 }
 
+#[test]
+fn encode_allowlisted_script_by_hash() {
+    let allow_list = allowing_script_hashes();
+    let hash = script_code_hash(&PEER_TO_PEER);
+
+    let recipient = AccountAddress::random();
+    let args = vec![
+        TransactionArgument::Address(recipient),
+        TransactionArgument::U64(100),
+    ];
+    let program = encode_allowlisted_script(hash, &allow_list, args).unwrap();
+    assert_eq!(program, encode_transfer_program(&recipient, 100));
+
+    // The hash of a stdlib script is rejected when it isn't in the allow-list.
+    let others: Vec<_> = allow_list.into_iter().filter(|h| *h != hash).collect();
+    assert_matches!(
+        encode_allowlisted_script(hash, &others, vec![]),
+        Err(EncodeError::NotAllowListed(_))
+    );
+
+    // An allow-listed hash that doesn't belong to any stdlib script is rejected as well.
+    let unknown = [0u8; SCRIPT_HASH_LENGTH];
+    assert_matches!(
+        encode_allowlisted_script(unknown, &[unknown], vec![]),
+        Err(EncodeError::UnknownScript(_))
+    );
+}
+
 #[test]
 fn verify_simple_payment() {
     // create a FakeExecutor with a genesis from file
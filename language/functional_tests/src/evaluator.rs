@@ -14,9 +14,11 @@ use language_e2e_tests::{
 };
 use std::{str::FromStr, time::Duration};
 use stdlib::stdlib_modules;
-use transaction_builder::transaction::{make_transaction_program, serialize_program};
+use transaction_builder::transaction::serialize_program;
 use types::{
-    transaction::{RawTransaction, TransactionArgument, TransactionOutput, TransactionStatus},
+    transaction::{
+        Program, RawTransaction, TransactionArgument, TransactionOutput, TransactionStatus,
+    },
     vm_error::{ExecutionStatus, VMStatus},
 };
 use vm::{
@@ -120,7 +122,10 @@ fn run_transaction(
 ) -> Result<TransactionOutput> {
     let account = data.account();
 
-    let program = make_transaction_program(program, args)?;
+    // The arguments are deliberately not checked against `main` here, so that tests can exercise
+    // how the VM handles mismatched arguments.
+    let (script_blob, module_blobs) = serialize_program(program)?;
+    let program = Program::new(script_blob, module_blobs, args.to_vec());
     let account_resource = exec.read_account_resource(&account).unwrap();
 
     let transaction = RawTransaction::new(
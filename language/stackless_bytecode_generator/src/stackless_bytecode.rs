@@ -32,6 +32,7 @@ pub enum StacklessBytecode {
 
     GetGasRemaining(TempIndex),
     GetTxnSequenceNumber(TempIndex),
+    GetTxnExpirationTime(TempIndex),
     GetTxnPublicKey(TempIndex),
     GetTxnSenderAddress(TempIndex),
     GetTxnMaxGasUnits(TempIndex),
@@ -695,6 +695,16 @@ impl<'a> StacklessBytecodeGenerator<'a> {
                 );
                 self.temp_count += 1;
             }
+            Bytecode::GetTxnExpirationTime => {
+                let temp_index = self.temp_count;
+                self.temp_stack.push(temp_index);
+                self.local_types.push(SignatureToken::U64);
+                self.insert_stackless_bytecode(
+                    StacklessBytecode::GetTxnExpirationTime(temp_index),
+                    offset,
+                );
+                self.temp_count += 1;
+            }
 
             Bytecode::GetTxnSenderAddress => {
                 let temp_index = self.temp_count;
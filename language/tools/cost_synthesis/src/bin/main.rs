@@ -107,6 +107,7 @@ fn stack_instructions() {
         GetTxnSenderAddress,
         GetTxnSequenceNumber,
         GetTxnPublicKey,
+        GetTxnExpirationTime,
     ];
 
     let mod_gen: ModuleGenerator = ModuleGenerator::new(NUM_ITERS as u16, 3);
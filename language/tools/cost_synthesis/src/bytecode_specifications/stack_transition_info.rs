@@ -253,6 +253,7 @@ pub fn call_details(op: &Bytecode) -> Vec<CallDetails> {
         }
         Bytecode::GetTxnGasUnitPrice
         | Bytecode::GetTxnSequenceNumber
+        | Bytecode::GetTxnExpirationTime
         | Bytecode::GetTxnMaxGasUnits
         | Bytecode::GetGasRemaining => type_transition! { empty() => u64s(1) },
         Bytecode::GetTxnSenderAddress => type_transition! { empty() => simple_addrs(1) },
@@ -43,6 +43,7 @@ macro_rules! with_loaded_vm {
         let mut $vm =
             TransactionExecutor::new(&$module_cache, &data_cache, TransactionMetadata::default());
         $vm.turn_off_gas_metering();
+        $vm.set_end_of_code_is_return(true);
         $vm.execution_stack.push_frame(entry_func);
     };
 }
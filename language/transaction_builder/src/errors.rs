@@ -1,7 +1,30 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-pub use failure::Error;
+use failure::prelude::*;
+
+/// Errors that can occur while encoding a transaction.
+#[derive(Debug, Fail)]
+pub enum EncodeError {
+    /// The script or one of the modules could not be serialized.
+    #[fail(display = "Serialization error: {}", _0)]
+    Serialization(Error),
+    /// An argument does not match what the script expects.
+    #[fail(display = "Validation error: {}", _0)]
+    Validation(String),
+    /// The number of arguments does not match the script's signature.
+    #[fail(
+        display = "Arity error: expected {} arguments, got {}",
+        expected, actual
+    )]
+    Arity { expected: usize, actual: usize },
+    /// The script hash is not in the allow-list.
+    #[fail(display = "Script hash {} is not allow-listed", _0)]
+    NotAllowListed(String),
+    /// The script hash is allow-listed but does not belong to any known script.
+    #[fail(display = "No known script has hash {}", _0)]
+    UnknownScript(String),
+}
 
 /// The common result type used in this crate.
-pub type Result<T> = std::result::Result<T, Error>;
+pub type Result<T> = std::result::Result<T, EncodeError>;
@@ -3,3 +3,5 @@
 
 mod errors;
 pub mod transaction;
+
+pub use errors::EncodeError;
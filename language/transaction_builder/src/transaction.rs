@@ -2,20 +2,41 @@
 // SPDX-License-Identifier: Apache-2.0
 
 use crate::errors::*;
-use types::transaction::{Program, TransactionArgument};
-use vm::file_format::CompiledProgram;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tiny_keccak::Keccak;
+use types::{
+    account_address::AccountAddress,
+    transaction::{Program, RawTransaction, TransactionArgument, SCRIPT_HASH_LENGTH},
+};
+use vm::{
+    access::ScriptAccess,
+    file_format::{
+        AddressPoolIndex, Bytecode, CodeUnit, CompiledModule, CompiledProgram, CompiledScript,
+        CompiledScriptMut, FunctionDefinition, FunctionHandle, FunctionHandleIndex,
+        FunctionSignature, FunctionSignatureIndex, LocalsSignature, LocalsSignatureIndex,
+        ModuleHandle, ModuleHandleIndex, SignatureToken, StringPoolIndex,
+    },
+};
+
+#[cfg(test)]
+#[path = "unit_tests/transaction_tests.rs"]
+mod transaction_tests;
 
 /// Serializes the given script and modules to be published.
 pub fn serialize_program(program: &CompiledProgram) -> Result<(Vec<u8>, Vec<Vec<u8>>)> {
     let mut script_blob = vec![];
-    program.script.serialize(&mut script_blob)?;
+    program
+        .script
+        .serialize(&mut script_blob)
+        .map_err(EncodeError::Serialization)?;
 
     let module_blobs = program
         .modules
         .iter()
         .map(|m| {
             let mut module_blob = vec![];
-            m.serialize(&mut module_blob)?;
+            m.serialize(&mut module_blob)
+                .map_err(EncodeError::Serialization)?;
             Ok(module_blob)
         })
         .collect::<Result<Vec<_>>>()?;
@@ -23,12 +44,145 @@ pub fn serialize_program(program: &CompiledProgram) -> Result<(Vec<u8>, Vec<Vec<
     Ok((script_blob, module_blobs))
 }
 
+/// Checks that the transaction arguments match the signature of the program's `main` function.
+///
+/// The VM performs the same check when the transaction is verified; this allows callers to catch
+/// the mismatch before submitting the transaction.
+pub fn check_program_args(program: &CompiledProgram, args: &[TransactionArgument]) -> Result<()> {
+    let script = &program.script;
+    let main_handle = script.function_handle_at(script.main().function);
+    let signature = script.function_signature_at(main_handle.signature);
+    if signature.arg_types.len() != args.len() {
+        return Err(EncodeError::Arity {
+            expected: signature.arg_types.len(),
+            actual: args.len(),
+        });
+    }
+    for (idx, (ty, arg)) in signature.arg_types.iter().zip(args.iter()).enumerate() {
+        match (ty, arg) {
+            (SignatureToken::U64, TransactionArgument::U64(_))
+            | (SignatureToken::Address, TransactionArgument::Address(_))
+            | (SignatureToken::ByteArray, TransactionArgument::ByteArray(_))
+            | (SignatureToken::String, TransactionArgument::String(_)) => (),
+            _ => {
+                return Err(EncodeError::Validation(format!(
+                    "argument {} is {:?} but main expects {:?}",
+                    idx, arg, ty
+                )))
+            }
+        }
+    }
+    Ok(())
+}
+
 /// Creates a transaction program by serializing the the given `CompiledProgram` and
-/// bundling it with transaction arguments.
+/// bundling it with transaction arguments. Fails if the arguments don't match the signature of
+/// the program's `main` function, see [`check_program_args`].
 pub fn make_transaction_program(
     program: &CompiledProgram,
     args: &[TransactionArgument],
 ) -> Result<Program> {
+    check_program_args(program, args)?;
     let (script_blob, module_blobs) = serialize_program(program)?;
     Ok(Program::new(script_blob, module_blobs, args.to_vec()))
 }
+
+/// The gas unit price used by [`into_raw_transaction`].
+pub const DEFAULT_GAS_UNIT_PRICE: u64 = 0;
+
+/// How long, from the time it is built, a transaction from [`into_raw_transaction`] stays valid.
+pub const DEFAULT_EXPIRATION_DELAY: Duration = Duration::from_secs(100);
+
+/// Wraps an encoded `program` into a `RawTransaction` from `sender`, ready to be signed.
+///
+/// The transaction pays `DEFAULT_GAS_UNIT_PRICE` per gas unit and expires
+/// `DEFAULT_EXPIRATION_DELAY` from now, rounded down to the second like the client does. Use
+/// `RawTransaction::new` to pick those explicitly.
+pub fn into_raw_transaction(
+    program: Program,
+    sender: AccountAddress,
+    sequence_number: u64,
+    max_gas_amount: u64,
+) -> RawTransaction {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .expect("system time is before the Unix epoch");
+    RawTransaction::new(
+        sender,
+        sequence_number,
+        program,
+        max_gas_amount,
+        DEFAULT_GAS_UNIT_PRICE,
+        Duration::from_secs(now.as_secs()) + DEFAULT_EXPIRATION_DELAY,
+    )
+}
+
+/// Computes the hash of the script in `program`.
+///
+/// This is the SHA3-256 of the serialized script, the same hash the VM looks up in the script
+/// whitelist of `VMPublishingOption::Locked`. Modules and arguments do not contribute to it.
+pub fn script_hash(program: &Program) -> [u8; SCRIPT_HASH_LENGTH] {
+    script_code_hash(program.code())
+}
+
+/// Computes the hash of a serialized script, as used by [`script_hash`].
+pub fn script_code_hash(code: &[u8]) -> [u8; SCRIPT_HASH_LENGTH] {
+    let mut hash = [0u8; SCRIPT_HASH_LENGTH];
+    let mut keccak = Keccak::new_sha3_256();
+    keccak.update(code);
+    keccak.finalize(&mut hash);
+    hash
+}
+
+/// Creates a transaction program that publishes the module serialized in `module_bytes`.
+///
+/// Modules are published by bundling them with a script, so the module is paired with a script
+/// that takes no arguments and simply returns.
+pub fn encode_publish_module(module_bytes: Vec<u8>) -> Result<Program> {
+    CompiledModule::deserialize(&module_bytes).map_err(|err| {
+        EncodeError::Validation(format!("module failed to deserialize: {:?}", err))
+    })?;
+
+    let mut script_blob = vec![];
+    return_script(vec![])
+        .serialize(&mut script_blob)
+        .map_err(EncodeError::Serialization)?;
+    Ok(Program::new(script_blob, vec![module_bytes], vec![]))
+}
+
+/// A script whose `main` takes arguments of `arg_types` and returns immediately.
+pub(crate) fn return_script(arg_types: Vec<SignatureToken>) -> CompiledScript {
+    CompiledScriptMut {
+        main: FunctionDefinition {
+            function: FunctionHandleIndex::new(0),
+            flags: CodeUnit::PUBLIC,
+            code: CodeUnit {
+                max_stack_size: 0,
+                locals: LocalsSignatureIndex(0),
+                code: vec![Bytecode::Ret],
+            },
+        },
+        module_handles: vec![ModuleHandle {
+            address: AddressPoolIndex::new(0),
+            name: StringPoolIndex::new(0),
+        }],
+        struct_handles: vec![],
+        function_handles: vec![FunctionHandle {
+            name: StringPoolIndex::new(1),
+            signature: FunctionSignatureIndex::new(0),
+            module: ModuleHandleIndex::new(0),
+        }],
+        type_signatures: vec![],
+        function_signatures: vec![FunctionSignature {
+            arg_types: arg_types.clone(),
+            return_types: vec![],
+            kind_constraints: vec![],
+        }],
+        locals_signatures: vec![LocalsSignature(arg_types)],
+        string_pool: vec!["<SELF>".to_string(), "main".to_string()],
+        byte_array_pool: vec![],
+        address_pool: vec![AccountAddress::default()],
+    }
+    .freeze()
+    .expect("return script should satisfy bounds checker")
+}
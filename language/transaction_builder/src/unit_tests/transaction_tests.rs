@@ -0,0 +1,158 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{errors::EncodeError, transaction::*};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use types::{
+    account_address::AccountAddress,
+    byte_array::ByteArray,
+    transaction::{Program, RawTransaction, TransactionArgument},
+};
+use vm::file_format::{
+    AddressPoolIndex, Bytecode, CompiledModuleMut, CompiledProgram, CompiledScript, ModuleHandle,
+    SignatureToken, StringPoolIndex,
+};
+
+fn program_with_main(arg_types: Vec<SignatureToken>, code: Vec<Bytecode>) -> CompiledProgram {
+    let mut script = return_script(arg_types).into_inner();
+    script.main.code.code = code;
+    let script = script
+        .freeze()
+        .expect("test script should satisfy bounds checker");
+    CompiledProgram::new(vec![], script)
+}
+
+#[test]
+fn serialization_error() {
+    // Code units are limited to u16::max_value() instructions.
+    let code = vec![Bytecode::Ret; u16::max_value() as usize + 1];
+    let program = program_with_main(vec![], code);
+    match serialize_program(&program) {
+        Err(EncodeError::Serialization(_)) => (),
+        res => panic!("expected serialization error, got {:?}", res),
+    }
+    match make_transaction_program(&program, &[]) {
+        Err(EncodeError::Serialization(_)) => (),
+        res => panic!("expected serialization error, got {:?}", res),
+    }
+}
+
+#[test]
+fn arity_error() {
+    let program = program_with_main(
+        vec![SignatureToken::U64, SignatureToken::Address],
+        vec![Bytecode::Ret],
+    );
+    match check_program_args(&program, &[TransactionArgument::U64(1)]) {
+        Err(EncodeError::Arity {
+            expected: 2,
+            actual: 1,
+        }) => (),
+        res => panic!("expected arity error, got {:?}", res),
+    }
+    match make_transaction_program(&program, &[TransactionArgument::U64(1)]) {
+        Err(EncodeError::Arity { .. }) => (),
+        res => panic!("expected arity error, got {:?}", res),
+    }
+}
+
+#[test]
+fn validation_error() {
+    let program = program_with_main(
+        vec![SignatureToken::U64, SignatureToken::ByteArray],
+        vec![Bytecode::Ret],
+    );
+    match check_program_args(
+        &program,
+        &[
+            TransactionArgument::U64(1),
+            TransactionArgument::Address(AccountAddress::default()),
+        ],
+    ) {
+        Err(EncodeError::Validation(_)) => (),
+        res => panic!("expected validation error, got {:?}", res),
+    }
+    check_program_args(
+        &program,
+        &[
+            TransactionArgument::U64(1),
+            TransactionArgument::ByteArray(ByteArray::new(vec![1, 2, 3])),
+        ],
+    )
+    .unwrap();
+}
+
+#[test]
+fn publish_module() {
+    let module = CompiledModuleMut {
+        module_handles: vec![ModuleHandle {
+            address: AddressPoolIndex::new(0),
+            name: StringPoolIndex::new(0),
+        }],
+        string_pool: vec!["M".to_string()],
+        address_pool: vec![AccountAddress::default()],
+        ..CompiledModuleMut::default()
+    }
+    .freeze()
+    .expect("test module should satisfy bounds checker");
+    let mut module_bytes = vec![];
+    module.serialize(&mut module_bytes).unwrap();
+
+    let program = encode_publish_module(module_bytes.clone()).unwrap();
+    assert_eq!(program.modules(), &[module_bytes]);
+    assert!(program.args().is_empty());
+    CompiledScript::deserialize(program.code()).expect("script should deserialize");
+}
+
+#[test]
+fn publish_module_garbage() {
+    match encode_publish_module(vec![0xde, 0xad, 0xbe, 0xef]) {
+        Err(EncodeError::Validation(_)) => (),
+        res => panic!("expected validation error, got {:?}", res),
+    }
+}
+
+#[test]
+fn script_hash_is_sha3_of_code() {
+    let program = Program::new(b"abc".to_vec(), vec![], vec![]);
+    assert_eq!(
+        hex::encode(script_hash(&program)),
+        "3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532"
+    );
+
+    // Modules and arguments are not part of the script hash.
+    let with_extras = Program::new(
+        b"abc".to_vec(),
+        vec![vec![1, 2, 3]],
+        vec![TransactionArgument::U64(7)],
+    );
+    assert_eq!(script_hash(&with_extras), script_hash(&program));
+}
+
+#[test]
+fn into_raw_transaction_fills_in_defaults() {
+    let program = Program::new(vec![1, 2, 3], vec![], vec![TransactionArgument::U64(7)]);
+    let sender = AccountAddress::random();
+    let now = || {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_secs()
+    };
+    let before = now();
+    let raw_txn = into_raw_transaction(program.clone(), sender, 3, 10_000);
+    let after = now();
+
+    // The clock may tick between reading it here and in `into_raw_transaction`.
+    assert!((before..=after).any(|secs| {
+        raw_txn
+            == RawTransaction::new(
+                sender,
+                3,
+                program.clone(),
+                10_000,
+                DEFAULT_GAS_UNIT_PRICE,
+                Duration::from_secs(secs) + DEFAULT_EXPIRATION_DELAY,
+            )
+    }));
+}
@@ -329,7 +329,7 @@ impl FunctionDefinition {
                     | Or | And | Not | Eq | Neq | Lt | Gt | Le | Ge | Abort
                     | GetTxnGasUnitPrice | GetTxnMaxGasUnits | GetGasRemaining
                     | GetTxnSenderAddress | CreateAccount | EmitEvent | GetTxnSequenceNumber
-                    | GetTxnPublicKey => None,
+                    | GetTxnPublicKey | GetTxnExpirationTime => None,
                 }
             })
             .collect()
@@ -1,14 +1,19 @@
 // Copyright (c) The Libra Core Contributors
 // SPDX-License-Identifier: Apache-2.0
 
-use crate::{file_format::SignatureToken, IndexKind, SignatureTokenKind};
+use crate::{
+    file_format::{CodeOffset, SignatureToken},
+    IndexKind, SignatureTokenKind,
+};
 use failure::Fail;
 use std::{fmt, iter::FromIterator};
 use types::{
     account_address::AccountAddress,
     language_storage::ModuleId,
     transaction::TransactionStatus,
-    vm_error::{VMStatus, VMValidationStatus, VMVerificationError, VMVerificationStatus},
+    vm_error::{
+        AbortLocation, VMStatus, VMValidationStatus, VMVerificationError, VMVerificationStatus,
+    },
 };
 
 // We may want to eventually move this into the VM runtime since it is a semantic decision that
@@ -28,10 +33,14 @@ pub struct VMRuntimeError {
     pub err: VMErrorKind,
 }
 
-// TODO: Fill in the details for Locations. Ideally it should be a unique handle into a function and
-// a pc.
+/// Where a runtime error was raised. Either part may be unknown, e.g. for errors raised outside of
+/// any function.
+// TODO: Ideally this should be a unique handle into a function, i.e. include the module as well.
 #[derive(Debug, Default)]
-pub struct Location {}
+pub struct Location {
+    function: Option<String>,
+    code_offset: Option<CodeOffset>,
+}
 
 #[derive(Debug, PartialEq)]
 pub enum VMErrorKind {
@@ -50,6 +59,10 @@ pub enum VMErrorKind {
     CannotWriteExistingResource,
     ValueSerializerError,
     ValueDeserializerError,
+    ValueDepthExceeded,
+    TxnFieldUnavailable,
+    ExecutionLimitReached,
+    UnsafeRetUnusedResources,
     CodeSerializerError(BinaryError),
     CodeDeserializerError(BinaryError),
     Verification(Vec<VerificationStatus>),
@@ -321,6 +334,13 @@ pub enum VMInvariantViolation {
     StorageError,
     #[fail(display = "Internal runtime type error due to incorrect bytecode verification")]
     InternalTypeError,
+    #[fail(
+        display = "Value stack height is {} at function return (expected {})",
+        _0, _1
+    )]
+    StackImbalance(usize, usize),
+    #[fail(display = "Gas schedule has no entry for instruction key {}", _0)]
+    GasScheduleError(u8),
 }
 
 /// Error codes that can be emitted by the prologue. These have special significance to the VM when
@@ -343,7 +363,41 @@ pub type VMResult<T> = ::std::result::Result<VMRuntimeResult<T>, VMInvariantViol
 
 impl Location {
     pub fn new() -> Self {
-        Location {}
+        Location::default()
+    }
+
+    /// The location of an error raised in the function named `function`.
+    pub fn in_function(function: impl Into<String>) -> Self {
+        Location {
+            function: Some(function.into()),
+            code_offset: None,
+        }
+    }
+
+    /// Attaches the offset of the instruction that raised the error.
+    pub fn at_offset(mut self, code_offset: CodeOffset) -> Self {
+        self.code_offset = Some(code_offset);
+        self
+    }
+
+    pub fn function(&self) -> Option<&str> {
+        self.function.as_ref().map(String::as_str)
+    }
+
+    pub fn code_offset(&self) -> Option<CodeOffset> {
+        self.code_offset
+    }
+
+    /// The location as reported in the status of an aborted transaction, if both the function and
+    /// the offset are known.
+    pub fn abort_location(&self) -> Option<AbortLocation> {
+        match (&self.function, self.code_offset) {
+            (Some(function), Some(code_offset)) => Some(AbortLocation {
+                function: function.clone(),
+                code_offset,
+            }),
+            _ => None,
+        }
     }
 }
 
@@ -502,6 +556,10 @@ impl From<&VMInvariantViolation> for VMStatus {
             }
             VMInvariantViolation::StorageError => VMInvariantViolationError::StorageError,
             VMInvariantViolation::InternalTypeError => VMInvariantViolationError::InternalTypeError,
+            VMInvariantViolation::StackImbalance(_, _) => VMInvariantViolationError::StackImbalance,
+            VMInvariantViolation::GasScheduleError(_) => {
+                VMInvariantViolationError::GasScheduleError
+            }
         };
         VMStatus::InvariantViolation(err)
     }
@@ -733,7 +791,7 @@ impl From<&VMErrorKind> for VMStatus {
             VMErrorKind::ArithmeticError => {
                 ExecutionStatus::ArithmeticError(ArithmeticErrorType::Underflow)
             }
-            VMErrorKind::Aborted(err_code) => ExecutionStatus::Aborted(*err_code),
+            VMErrorKind::Aborted(err_code) => ExecutionStatus::Aborted(*err_code, None),
             VMErrorKind::OutOfGasError => ExecutionStatus::OutOfGas,
             VMErrorKind::TypeError => ExecutionStatus::TypeError,
             VMErrorKind::GlobalRefAlreadyReleased => ExecutionStatus::DynamicReferenceError(
@@ -754,6 +812,10 @@ impl From<&VMErrorKind> for VMStatus {
             }
             VMErrorKind::ValueSerializerError => ExecutionStatus::ValueSerializationError,
             VMErrorKind::ValueDeserializerError => ExecutionStatus::ValueDeserializationError,
+            VMErrorKind::ValueDepthExceeded => ExecutionStatus::ValueDepthExceeded,
+            VMErrorKind::TxnFieldUnavailable => ExecutionStatus::TxnFieldUnavailable,
+            VMErrorKind::ExecutionLimitReached => ExecutionStatus::ExecutionLimitReached,
+            VMErrorKind::UnsafeRetUnusedResources => ExecutionStatus::UnsafeRetUnusedResources,
             VMErrorKind::DuplicateModuleName => ExecutionStatus::DuplicateModuleName,
             // The below errors already have top-level VMStatus variants associated with them, so
             // return those.
@@ -767,6 +829,13 @@ impl From<&VMErrorKind> for VMStatus {
 
 impl From<&VMRuntimeError> for VMStatus {
     fn from(error: &VMRuntimeError) -> Self {
-        VMStatus::from(&error.err)
+        use types::vm_error::ExecutionStatus;
+        match error.err {
+            VMErrorKind::Aborted(err_code) => VMStatus::Execution(ExecutionStatus::Aborted(
+                err_code,
+                error.loc.abort_location(),
+            )),
+            ref err => VMStatus::from(err),
+        }
     }
 }
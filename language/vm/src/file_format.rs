@@ -1014,6 +1014,13 @@ pub enum Bytecode {
     ///
     /// ```..., -> ..., bytearray_value```
     GetTxnPublicKey,
+    /// Get the expiration time (in seconds) submitted with the transaction and pushes it on the
+    /// stack.
+    ///
+    /// Stack transition:
+    ///
+    /// ```... -> ..., u64_value```
+    GetTxnExpirationTime,
 }
 
 impl ::std::fmt::Debug for Bytecode {
@@ -1072,6 +1079,7 @@ impl ::std::fmt::Debug for Bytecode {
             Bytecode::EmitEvent => write!(f, "EmitEvent"),
             Bytecode::GetTxnSequenceNumber => write!(f, "GetTxnSequenceNumber"),
             Bytecode::GetTxnPublicKey => write!(f, "GetTxnPublicKey"),
+            Bytecode::GetTxnExpirationTime => write!(f, "GetTxnExpirationTime"),
         }
     }
 }
@@ -151,6 +151,7 @@ pub enum Opcodes {
     GET_TXN_SEQUENCE_NUMBER = 0x33,
     GET_TXN_PUBLIC_KEY      = 0x34,
     FREEZE_REF              = 0x35,
+    GET_TXN_EXPIRATION_TIME = 0x36,
 }
 
 /// Upper limit on the binary size
@@ -7,15 +7,17 @@
 //! operations or other native operations; the cost of each native operation will be returned by the
 //! native function itself.
 use crate::{
+    errors::VMInvariantViolation,
     file_format::{
         AddressPoolIndex, ByteArrayPoolIndex, Bytecode, FieldDefinitionIndex, FunctionHandleIndex,
         StringPoolIndex, StructDefinitionIndex, NO_TYPE_ACTUALS,
     },
-    serializer::serialize_instruction,
+    file_format_common::Opcodes,
+    serializer::{instruction_opcode, opcode_name},
 };
 use lazy_static::lazy_static;
 use std::{
-    collections::HashMap,
+    fmt,
     ops::{Add, Div, Mul, Sub},
     u64,
 };
@@ -26,6 +28,27 @@ use types::transaction::MAX_TRANSACTION_SIZE_IN_BYTES;
 /// them and the functions defined in the `GasAlgebra` trait.
 pub type GasCarrier = u64;
 
+/// Saturating arithmetic over a gas carrier. Gas computations that can be influenced by the
+/// transaction sender use these operations so that adversarial inputs clamp at the carrier's
+/// maximum instead of overflowing.
+pub trait SaturatingArithmetic: Sized {
+    /// Add, saturating at the maximum value of the carrier.
+    fn saturating_add(self, other: Self) -> Self;
+
+    /// Multiply, saturating at the maximum value of the carrier.
+    fn saturating_mul(self, other: Self) -> Self;
+}
+
+impl SaturatingArithmetic for u64 {
+    fn saturating_add(self, other: Self) -> Self {
+        u64::saturating_add(self, other)
+    }
+
+    fn saturating_mul(self, other: Self) -> Self {
+        u64::saturating_mul(self, other)
+    }
+}
+
 /// A trait encoding the operations permitted on the underlying carrier for the gas unit, and how
 /// other gas-related units can interact with other units -- operations can only be performed
 /// across units with the same underlying carrier (i.e. as long as the underlying data is
@@ -36,6 +59,7 @@ where
         + Sub<Output = GasCarrier>
         + Div<Output = GasCarrier>
         + Mul<Output = GasCarrier>
+        + SaturatingArithmetic
         + Copy,
 {
     /// Project a value into the gas algebra.
@@ -95,6 +119,16 @@ where
     fn div(self, right: impl GasAlgebra<GasCarrier>) -> Self {
         self.map2(right, Div::div)
     }
+
+    /// Add the two `GasAlgebra`s together, saturating instead of overflowing.
+    fn saturating_add(self, right: impl GasAlgebra<GasCarrier>) -> Self {
+        self.map2(right, SaturatingArithmetic::saturating_add)
+    }
+
+    /// Multiply two `GasAlgebra`s together, saturating instead of overflowing.
+    fn saturating_mul(self, right: impl GasAlgebra<GasCarrier>) -> Self {
+        self.map2(right, SaturatingArithmetic::saturating_mul)
+    }
 }
 
 // We would really like to be able to implement the standard arithmetic traits over the GasAlgebra
@@ -191,62 +225,94 @@ lazy_static! {
     pub static ref LARGE_TRANSACTION_CUTOFF: AbstractMemorySize<GasCarrier> = AbstractMemorySize::new(600);
 }
 
-/// The cost tables, keyed by the serialized form of the bytecode instruction.  We use the
+/// The number of distinct instruction keys. Every `InstructionKey` is a valid index into tables of
+/// this size.
+pub const NUMBER_OF_INSTRUCTION_KEYS: usize = u8::max_value() as usize + 1;
+
+/// The cost tables, indexed by the serialized form of the bytecode instruction.  We use the
 /// serialized form as opposed to the instruction enum itself as the key since this will be the
 /// on-chain representation of bytecode instructions in the future.
+///
+/// The tables are laid out as arrays when the `CostTable` is constructed so that looking up the
+/// cost of an instruction on the hot path is a direct index.
 #[derive(Debug)]
 pub struct CostTable {
-    pub compute_table: HashMap<InstructionKey, GasUnits<GasCarrier>>,
-    pub memory_table: HashMap<InstructionKey, GasUnits<GasCarrier>>,
+    pub compute_table: Vec<Option<GasUnits<GasCarrier>>>,
+    pub memory_table: Vec<Option<GasUnits<GasCarrier>>>,
+    // The opcode of each entry, so that the table can be printed with names.
+    opcode_table: Vec<Option<Opcodes>>,
 }
 
 impl InstructionKey {
     /// The encoding of the instruction is the serialized form of it, but disregarding the
     /// serializtion of the instructions arguments.
     pub fn new(instruction: &Bytecode) -> Self {
-        let mut vec = Vec::new();
-        serialize_instruction(&mut vec, instruction).unwrap();
-        Self(vec[0])
+        Self(instruction_opcode(instruction) as u8)
     }
 }
 
 impl CostTable {
     pub fn new(instrs: Vec<(Bytecode, u64, u64)>) -> Self {
-        let mut compute_table = HashMap::new();
-        let mut memory_table = HashMap::new();
+        let mut compute_table = vec![None; NUMBER_OF_INSTRUCTION_KEYS];
+        let mut memory_table = vec![None; NUMBER_OF_INSTRUCTION_KEYS];
+        let mut opcode_table = vec![None; NUMBER_OF_INSTRUCTION_KEYS];
         for (instr, comp_cost, mem_cost) in instrs.into_iter() {
-            let code = InstructionKey::new(&instr);
-            compute_table.insert(code, GasUnits::new(comp_cost));
-            memory_table.insert(code, GasUnits::new(mem_cost));
+            let opcode = instruction_opcode(&instr);
+            let code = opcode as usize;
+            compute_table[code] = Some(GasUnits::new(comp_cost));
+            memory_table[code] = Some(GasUnits::new(mem_cost));
+            opcode_table[code] = Some(opcode);
         }
         Self {
             compute_table,
             memory_table,
+            opcode_table,
         }
     }
 
+    /// Returns `VMInvariantViolation::GasScheduleError` if the table has no entry for `instr`.
     pub fn memory_gas(
         &self,
         instr: &Bytecode,
         size_provider: AbstractMemorySize<GasCarrier>,
-    ) -> GasUnits<GasCarrier> {
-        let code = InstructionKey::new(instr);
-        self.memory_table
-            .get(&code)
-            .unwrap()
-            .map2(size_provider, Mul::mul)
+    ) -> Result<GasUnits<GasCarrier>, VMInvariantViolation> {
+        let key = InstructionKey::new(instr).0;
+        self.memory_table[key as usize]
+            .map(|cost| cost.map2(size_provider, Mul::mul))
+            .ok_or(VMInvariantViolation::GasScheduleError(key))
     }
 
+    /// Returns `VMInvariantViolation::GasScheduleError` if the table has no entry for `instr`.
     pub fn comp_gas(
         &self,
         instr: &Bytecode,
         size_provider: AbstractMemorySize<GasCarrier>,
-    ) -> GasUnits<GasCarrier> {
-        let code = InstructionKey::new(instr);
-        self.compute_table
-            .get(&code)
-            .unwrap()
-            .map2(size_provider, Mul::mul)
+    ) -> Result<GasUnits<GasCarrier>, VMInvariantViolation> {
+        let key = InstructionKey::new(instr).0;
+        self.compute_table[key as usize]
+            .map(|cost| cost.map2(size_provider, Mul::mul))
+            .ok_or(VMInvariantViolation::GasScheduleError(key))
+    }
+}
+
+/// Prints one `mnemonic: instruction_gas/memory_gas` line per instruction in the table, ordered by
+/// instruction key.
+impl fmt::Display for CostTable {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (key, opcode) in self.opcode_table.iter().enumerate() {
+            if let (Some(opcode), Some(comp_gas), Some(mem_gas)) =
+                (opcode, self.compute_table[key], self.memory_table[key])
+            {
+                writeln!(
+                    f,
+                    "{}: {}/{}",
+                    opcode_name(*opcode),
+                    comp_gas.get(),
+                    mem_gas.get()
+                )?;
+            }
+        }
+        Ok(())
     }
 }
 
@@ -306,6 +372,9 @@ lazy_static! {
             (BitOr, 45, 1),
             (GetTxnMaxGasUnits, 34, 1),
             (GetTxnSequenceNumber, 29, 1),
+            // Executes exactly like GetTxnSequenceNumber: a u64 field read from the transaction
+            // metadata and pushed onto the stack, so it costs the same.
+            (GetTxnExpirationTime, 29, 1),
             (FreezeRef, 10, 1),
             (BorrowGlobal(StructDefinitionIndex::new(0), NO_TYPE_ACTUALS), 929, 1),
             (Div, 41, 1),
@@ -336,20 +405,27 @@ pub struct GasCost {
 pub fn static_cost_instr(
     instr: &Bytecode,
     size_provider: AbstractMemorySize<GasCarrier>,
-) -> GasCost {
-    GasCost {
-        instruction_gas: GAS_SCHEDULE.comp_gas(instr, size_provider),
-        memory_gas: GAS_SCHEDULE.memory_gas(instr, size_provider),
+) -> Result<GasCost, VMInvariantViolation> {
+    Ok(GasCost {
+        instruction_gas: GAS_SCHEDULE.comp_gas(instr, size_provider)?,
+        memory_gas: GAS_SCHEDULE.memory_gas(instr, size_provider)?,
+    })
+}
+
+impl AbstractMemorySize<GasCarrier> {
+    /// Computes the number of words that this size takes up, rounded up.
+    pub fn words(self) -> AbstractMemorySize<GasCarrier> {
+        precondition!(self.get() <= MAX_ABSTRACT_MEMORY_SIZE.get() - (WORD_SIZE.get() + 1));
+        // round-up div truncate
+        self.map2(*WORD_SIZE, |size, word_size| {
+            size.saturating_add(word_size - 1) / word_size
+        })
     }
 }
 
 /// Computes the number of words rounded up
 pub fn words_in(size: AbstractMemorySize<GasCarrier>) -> AbstractMemorySize<GasCarrier> {
-    precondition!(size.get() <= MAX_ABSTRACT_MEMORY_SIZE.get() - (WORD_SIZE.get() + 1));
-    // round-up div truncate
-    size.map2(*WORD_SIZE, |size, word_size| {
-        (size + (word_size - 1)) / word_size
-    })
+    size.words()
 }
 
 /// Calculate the intrinsic gas for the transaction based upon its size in bytes/words.
@@ -361,7 +437,7 @@ pub fn calculate_intrinsic_gas(
 
     if transaction_size.get() > LARGE_TRANSACTION_CUTOFF.get() {
         let excess = words_in(transaction_size.sub(*LARGE_TRANSACTION_CUTOFF));
-        min_transaction_fee.add(INTRINSIC_GAS_PER_BYTE.mul(excess))
+        min_transaction_fee.saturating_add(INTRINSIC_GAS_PER_BYTE.saturating_mul(excess))
     } else {
         min_transaction_fee.unitary_cast()
     }
@@ -399,6 +399,7 @@ impl BytecodeGen {
             EmitEvent,
             GetTxnSequenceNumber,
             GetTxnPublicKey,
+            GetTxnExpirationTime,
         ];
         select(JUST_BYTECODES)
     }
@@ -505,129 +505,193 @@ pub(crate) fn serialize_instruction(binary: &mut Vec<u8>, opcode: &Bytecode) ->
     Ok(())
 }
 
+/// Returns the opcode of `instruction`, i.e. the first byte of its serialized form, without
+/// serializing the instruction's arguments.
+pub(crate) fn instruction_opcode(instruction: &Bytecode) -> Opcodes {
+    match instruction {
+        Bytecode::FreezeRef => Opcodes::FREEZE_REF,
+        Bytecode::Pop => Opcodes::POP,
+        Bytecode::Ret => Opcodes::RET,
+        Bytecode::BrTrue(_) => Opcodes::BR_TRUE,
+        Bytecode::BrFalse(_) => Opcodes::BR_FALSE,
+        Bytecode::Branch(_) => Opcodes::BRANCH,
+        Bytecode::LdConst(_) => Opcodes::LD_CONST,
+        Bytecode::LdAddr(_) => Opcodes::LD_ADDR,
+        Bytecode::LdByteArray(_) => Opcodes::LD_BYTEARRAY,
+        Bytecode::LdStr(_) => Opcodes::LD_STR,
+        Bytecode::LdTrue => Opcodes::LD_TRUE,
+        Bytecode::LdFalse => Opcodes::LD_FALSE,
+        Bytecode::CopyLoc(_) => Opcodes::COPY_LOC,
+        Bytecode::MoveLoc(_) => Opcodes::MOVE_LOC,
+        Bytecode::StLoc(_) => Opcodes::ST_LOC,
+        Bytecode::BorrowLoc(_) => Opcodes::LD_REF_LOC,
+        Bytecode::BorrowField(_) => Opcodes::LD_REF_FIELD,
+        Bytecode::Call(_, _) => Opcodes::CALL,
+        Bytecode::Pack(_, _) => Opcodes::PACK,
+        Bytecode::Unpack(_, _) => Opcodes::UNPACK,
+        Bytecode::ReadRef => Opcodes::READ_REF,
+        Bytecode::WriteRef => Opcodes::WRITE_REF,
+        Bytecode::Add => Opcodes::ADD,
+        Bytecode::Sub => Opcodes::SUB,
+        Bytecode::Mul => Opcodes::MUL,
+        Bytecode::Mod => Opcodes::MOD,
+        Bytecode::Div => Opcodes::DIV,
+        Bytecode::BitOr => Opcodes::BIT_OR,
+        Bytecode::BitAnd => Opcodes::BIT_AND,
+        Bytecode::Xor => Opcodes::XOR,
+        Bytecode::Or => Opcodes::OR,
+        Bytecode::And => Opcodes::AND,
+        Bytecode::Not => Opcodes::NOT,
+        Bytecode::Eq => Opcodes::EQ,
+        Bytecode::Neq => Opcodes::NEQ,
+        Bytecode::Lt => Opcodes::LT,
+        Bytecode::Gt => Opcodes::GT,
+        Bytecode::Le => Opcodes::LE,
+        Bytecode::Ge => Opcodes::GE,
+        Bytecode::Abort => Opcodes::ABORT,
+        Bytecode::GetTxnGasUnitPrice => Opcodes::GET_TXN_GAS_UNIT_PRICE,
+        Bytecode::GetTxnMaxGasUnits => Opcodes::GET_TXN_MAX_GAS_UNITS,
+        Bytecode::GetGasRemaining => Opcodes::GET_GAS_REMAINING,
+        Bytecode::GetTxnSenderAddress => Opcodes::GET_TXN_SENDER,
+        Bytecode::Exists(_, _) => Opcodes::EXISTS,
+        Bytecode::BorrowGlobal(_, _) => Opcodes::BORROW_REF,
+        Bytecode::ReleaseRef => Opcodes::RELEASE_REF,
+        Bytecode::MoveFrom(_, _) => Opcodes::MOVE_FROM,
+        Bytecode::MoveToSender(_, _) => Opcodes::MOVE_TO,
+        Bytecode::CreateAccount => Opcodes::CREATE_ACCOUNT,
+        Bytecode::EmitEvent => Opcodes::EMIT_EVENT,
+        Bytecode::GetTxnSequenceNumber => Opcodes::GET_TXN_SEQUENCE_NUMBER,
+        Bytecode::GetTxnPublicKey => Opcodes::GET_TXN_PUBLIC_KEY,
+        Bytecode::GetTxnExpirationTime => Opcodes::GET_TXN_EXPIRATION_TIME,
+    }
+}
+
+/// Returns the name of the `Bytecode` instruction that serializes to `opcode`. This is the inverse
+/// of `instruction_opcode`, used to print instructions when their arguments don't matter.
+pub(crate) fn opcode_name(opcode: Opcodes) -> &'static str {
+    match opcode {
+        Opcodes::FREEZE_REF => "FreezeRef",
+        Opcodes::POP => "Pop",
+        Opcodes::RET => "Ret",
+        Opcodes::BR_TRUE => "BrTrue",
+        Opcodes::BR_FALSE => "BrFalse",
+        Opcodes::BRANCH => "Branch",
+        Opcodes::LD_CONST => "LdConst",
+        Opcodes::LD_ADDR => "LdAddr",
+        Opcodes::LD_BYTEARRAY => "LdByteArray",
+        Opcodes::LD_STR => "LdStr",
+        Opcodes::LD_TRUE => "LdTrue",
+        Opcodes::LD_FALSE => "LdFalse",
+        Opcodes::COPY_LOC => "CopyLoc",
+        Opcodes::MOVE_LOC => "MoveLoc",
+        Opcodes::ST_LOC => "StLoc",
+        Opcodes::LD_REF_LOC => "BorrowLoc",
+        Opcodes::LD_REF_FIELD => "BorrowField",
+        Opcodes::CALL => "Call",
+        Opcodes::PACK => "Pack",
+        Opcodes::UNPACK => "Unpack",
+        Opcodes::READ_REF => "ReadRef",
+        Opcodes::WRITE_REF => "WriteRef",
+        Opcodes::ADD => "Add",
+        Opcodes::SUB => "Sub",
+        Opcodes::MUL => "Mul",
+        Opcodes::MOD => "Mod",
+        Opcodes::DIV => "Div",
+        Opcodes::BIT_OR => "BitOr",
+        Opcodes::BIT_AND => "BitAnd",
+        Opcodes::XOR => "Xor",
+        Opcodes::OR => "Or",
+        Opcodes::AND => "And",
+        Opcodes::NOT => "Not",
+        Opcodes::EQ => "Eq",
+        Opcodes::NEQ => "Neq",
+        Opcodes::LT => "Lt",
+        Opcodes::GT => "Gt",
+        Opcodes::LE => "Le",
+        Opcodes::GE => "Ge",
+        Opcodes::ABORT => "Abort",
+        Opcodes::GET_TXN_GAS_UNIT_PRICE => "GetTxnGasUnitPrice",
+        Opcodes::GET_TXN_MAX_GAS_UNITS => "GetTxnMaxGasUnits",
+        Opcodes::GET_GAS_REMAINING => "GetGasRemaining",
+        Opcodes::GET_TXN_SENDER => "GetTxnSenderAddress",
+        Opcodes::EXISTS => "Exists",
+        Opcodes::BORROW_REF => "BorrowGlobal",
+        Opcodes::RELEASE_REF => "ReleaseRef",
+        Opcodes::MOVE_FROM => "MoveFrom",
+        Opcodes::MOVE_TO => "MoveToSender",
+        Opcodes::CREATE_ACCOUNT => "CreateAccount",
+        Opcodes::EMIT_EVENT => "EmitEvent",
+        Opcodes::GET_TXN_SEQUENCE_NUMBER => "GetTxnSequenceNumber",
+        Opcodes::GET_TXN_PUBLIC_KEY => "GetTxnPublicKey",
+        Opcodes::GET_TXN_EXPIRATION_TIME => "GetTxnExpirationTime",
+    }
+}
+
 /// Serializes a single `Bytecode` instruction.
 fn serialize_instruction_inner(binary: &mut BinaryData, opcode: &Bytecode) -> Result<()> {
-    let res = match opcode {
-        Bytecode::FreezeRef => binary.push(Opcodes::FREEZE_REF as u8),
-        Bytecode::Pop => binary.push(Opcodes::POP as u8),
-        Bytecode::Ret => binary.push(Opcodes::RET as u8),
-        Bytecode::BrTrue(code_offset) => {
-            binary.push(Opcodes::BR_TRUE as u8)?;
-            write_u16(binary, *code_offset)
-        }
-        Bytecode::BrFalse(code_offset) => {
-            binary.push(Opcodes::BR_FALSE as u8)?;
-            write_u16(binary, *code_offset)
-        }
-        Bytecode::Branch(code_offset) => {
-            binary.push(Opcodes::BRANCH as u8)?;
-            write_u16(binary, *code_offset)
-        }
-        Bytecode::LdConst(value) => {
-            binary.push(Opcodes::LD_CONST as u8)?;
-            write_u64(binary, *value)
-        }
-        Bytecode::LdAddr(address_idx) => {
-            binary.push(Opcodes::LD_ADDR as u8)?;
-            write_u16_as_uleb128(binary, address_idx.0)
-        }
-        Bytecode::LdByteArray(byte_array_idx) => {
-            binary.push(Opcodes::LD_BYTEARRAY as u8)?;
-            write_u16_as_uleb128(binary, byte_array_idx.0)
-        }
-        Bytecode::LdStr(string_idx) => {
-            binary.push(Opcodes::LD_STR as u8)?;
-            write_u16_as_uleb128(binary, string_idx.0)
-        }
-        Bytecode::LdTrue => binary.push(Opcodes::LD_TRUE as u8),
-        Bytecode::LdFalse => binary.push(Opcodes::LD_FALSE as u8),
-        Bytecode::CopyLoc(local_idx) => {
-            binary.push(Opcodes::COPY_LOC as u8)?;
-            binary.push(*local_idx)
-        }
-        Bytecode::MoveLoc(local_idx) => {
-            binary.push(Opcodes::MOVE_LOC as u8)?;
-            binary.push(*local_idx)
-        }
-        Bytecode::StLoc(local_idx) => {
-            binary.push(Opcodes::ST_LOC as u8)?;
-            binary.push(*local_idx)
-        }
-        Bytecode::BorrowLoc(local_idx) => {
-            binary.push(Opcodes::LD_REF_LOC as u8)?;
-            binary.push(*local_idx)
-        }
-        Bytecode::BorrowField(field_idx) => {
-            binary.push(Opcodes::LD_REF_FIELD as u8)?;
-            write_u16_as_uleb128(binary, field_idx.0)
-        }
+    binary.push(instruction_opcode(opcode) as u8)?;
+    match opcode {
+        Bytecode::BrTrue(code_offset)
+        | Bytecode::BrFalse(code_offset)
+        | Bytecode::Branch(code_offset) => write_u16(binary, *code_offset),
+        Bytecode::LdConst(value) => write_u64(binary, *value),
+        Bytecode::LdAddr(address_idx) => write_u16_as_uleb128(binary, address_idx.0),
+        Bytecode::LdByteArray(byte_array_idx) => write_u16_as_uleb128(binary, byte_array_idx.0),
+        Bytecode::LdStr(string_idx) => write_u16_as_uleb128(binary, string_idx.0),
+        Bytecode::CopyLoc(local_idx)
+        | Bytecode::MoveLoc(local_idx)
+        | Bytecode::StLoc(local_idx)
+        | Bytecode::BorrowLoc(local_idx) => binary.push(*local_idx),
+        Bytecode::BorrowField(field_idx) => write_u16_as_uleb128(binary, field_idx.0),
         Bytecode::Call(method_idx, types_idx) => {
-            binary.push(Opcodes::CALL as u8)?;
             write_u16_as_uleb128(binary, method_idx.0)?;
             write_u16_as_uleb128(binary, types_idx.0)
         }
-        Bytecode::Pack(class_idx, types_idx) => {
-            binary.push(Opcodes::PACK as u8)?;
+        Bytecode::Pack(class_idx, types_idx)
+        | Bytecode::Unpack(class_idx, types_idx)
+        | Bytecode::Exists(class_idx, types_idx)
+        | Bytecode::BorrowGlobal(class_idx, types_idx)
+        | Bytecode::MoveFrom(class_idx, types_idx)
+        | Bytecode::MoveToSender(class_idx, types_idx) => {
             write_u16_as_uleb128(binary, class_idx.0)?;
             write_u16_as_uleb128(binary, types_idx.0)
         }
-        Bytecode::Unpack(class_idx, types_idx) => {
-            binary.push(Opcodes::UNPACK as u8)?;
-            write_u16_as_uleb128(binary, class_idx.0)?;
-            write_u16_as_uleb128(binary, types_idx.0)
-        }
-        Bytecode::ReadRef => binary.push(Opcodes::READ_REF as u8),
-        Bytecode::WriteRef => binary.push(Opcodes::WRITE_REF as u8),
-        Bytecode::Add => binary.push(Opcodes::ADD as u8),
-        Bytecode::Sub => binary.push(Opcodes::SUB as u8),
-        Bytecode::Mul => binary.push(Opcodes::MUL as u8),
-        Bytecode::Mod => binary.push(Opcodes::MOD as u8),
-        Bytecode::Div => binary.push(Opcodes::DIV as u8),
-        Bytecode::BitOr => binary.push(Opcodes::BIT_OR as u8),
-        Bytecode::BitAnd => binary.push(Opcodes::BIT_AND as u8),
-        Bytecode::Xor => binary.push(Opcodes::XOR as u8),
-        Bytecode::Or => binary.push(Opcodes::OR as u8),
-        Bytecode::And => binary.push(Opcodes::AND as u8),
-        Bytecode::Not => binary.push(Opcodes::NOT as u8),
-        Bytecode::Eq => binary.push(Opcodes::EQ as u8),
-        Bytecode::Neq => binary.push(Opcodes::NEQ as u8),
-        Bytecode::Lt => binary.push(Opcodes::LT as u8),
-        Bytecode::Gt => binary.push(Opcodes::GT as u8),
-        Bytecode::Le => binary.push(Opcodes::LE as u8),
-        Bytecode::Ge => binary.push(Opcodes::GE as u8),
-        Bytecode::Abort => binary.push(Opcodes::ABORT as u8),
-        Bytecode::GetTxnGasUnitPrice => binary.push(Opcodes::GET_TXN_GAS_UNIT_PRICE as u8),
-        Bytecode::GetTxnMaxGasUnits => binary.push(Opcodes::GET_TXN_MAX_GAS_UNITS as u8),
-        Bytecode::GetGasRemaining => binary.push(Opcodes::GET_GAS_REMAINING as u8),
-        Bytecode::GetTxnSenderAddress => binary.push(Opcodes::GET_TXN_SENDER as u8),
-        Bytecode::Exists(class_idx, types_idx) => {
-            binary.push(Opcodes::EXISTS as u8)?;
-            write_u16_as_uleb128(binary, class_idx.0)?;
-            write_u16_as_uleb128(binary, types_idx.0)
-        }
-        Bytecode::BorrowGlobal(class_idx, types_idx) => {
-            binary.push(Opcodes::BORROW_REF as u8)?;
-            write_u16_as_uleb128(binary, class_idx.0)?;
-            write_u16_as_uleb128(binary, types_idx.0)
-        }
-        Bytecode::ReleaseRef => binary.push(Opcodes::RELEASE_REF as u8),
-        Bytecode::MoveFrom(class_idx, types_idx) => {
-            binary.push(Opcodes::MOVE_FROM as u8)?;
-            write_u16_as_uleb128(binary, class_idx.0)?;
-            write_u16_as_uleb128(binary, types_idx.0)
-        }
-        Bytecode::MoveToSender(class_idx, types_idx) => {
-            binary.push(Opcodes::MOVE_TO as u8)?;
-            write_u16_as_uleb128(binary, class_idx.0)?;
-            write_u16_as_uleb128(binary, types_idx.0)
-        }
-        Bytecode::CreateAccount => binary.push(Opcodes::CREATE_ACCOUNT as u8),
-        Bytecode::EmitEvent => binary.push(Opcodes::EMIT_EVENT as u8),
-        Bytecode::GetTxnSequenceNumber => binary.push(Opcodes::GET_TXN_SEQUENCE_NUMBER as u8),
-        Bytecode::GetTxnPublicKey => binary.push(Opcodes::GET_TXN_PUBLIC_KEY as u8),
-    };
-    res?;
-    Ok(())
+        Bytecode::FreezeRef
+        | Bytecode::Pop
+        | Bytecode::Ret
+        | Bytecode::LdTrue
+        | Bytecode::LdFalse
+        | Bytecode::ReadRef
+        | Bytecode::WriteRef
+        | Bytecode::Add
+        | Bytecode::Sub
+        | Bytecode::Mul
+        | Bytecode::Mod
+        | Bytecode::Div
+        | Bytecode::BitOr
+        | Bytecode::BitAnd
+        | Bytecode::Xor
+        | Bytecode::Or
+        | Bytecode::And
+        | Bytecode::Not
+        | Bytecode::Eq
+        | Bytecode::Neq
+        | Bytecode::Lt
+        | Bytecode::Gt
+        | Bytecode::Le
+        | Bytecode::Ge
+        | Bytecode::Abort
+        | Bytecode::GetTxnGasUnitPrice
+        | Bytecode::GetTxnMaxGasUnits
+        | Bytecode::GetGasRemaining
+        | Bytecode::GetTxnSenderAddress
+        | Bytecode::ReleaseRef
+        | Bytecode::CreateAccount
+        | Bytecode::EmitEvent
+        | Bytecode::GetTxnSequenceNumber
+        | Bytecode::GetTxnPublicKey
+        | Bytecode::GetTxnExpirationTime => Ok(()),
+    }
 }
 
 /// Serializes a `Bytecode` stream. Serialization of the function body.
@@ -12,6 +12,7 @@ pub struct TransactionMetadata {
     pub max_gas_amount: GasUnits<GasCarrier>,
     pub gas_unit_price: GasPrice<GasCarrier>,
     pub transaction_size: AbstractMemorySize<GasCarrier>,
+    pub expiration_time: u64,
 }
 
 impl TransactionMetadata {
@@ -23,6 +24,7 @@ impl TransactionMetadata {
             max_gas_amount: GasUnits::new(txn.max_gas_amount()),
             gas_unit_price: GasPrice::new(txn.gas_unit_price()),
             transaction_size: AbstractMemorySize::new(txn.raw_txn_bytes_len() as u64),
+            expiration_time: txn.expiration_time().as_secs(),
         }
     }
 
@@ -49,6 +51,10 @@ impl TransactionMetadata {
     pub fn transaction_size(&self) -> AbstractMemorySize<GasCarrier> {
         self.transaction_size
     }
+
+    pub fn expiration_time(&self) -> u64 {
+        self.expiration_time
+    }
 }
 
 impl Default for TransactionMetadata {
@@ -61,6 +67,7 @@ impl Default for TransactionMetadata {
             max_gas_amount: GasUnits::new(100_000_000),
             gas_unit_price: GasPrice::new(0),
             transaction_size: AbstractMemorySize::new(0),
+            expiration_time: u64::max_value(),
         }
     }
 }
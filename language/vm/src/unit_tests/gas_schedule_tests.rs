@@ -0,0 +1,94 @@
+// Copyright (c) The Libra Core Contributors
+// SPDX-License-Identifier: Apache-2.0
+
+use crate::{
+    errors::VMInvariantViolation, file_format::Bytecode, gas_schedule::*,
+    serializer::serialize_instruction,
+};
+use proptest::prelude::*;
+
+#[test]
+fn words_rounds_up() {
+    assert_eq!(WORD_SIZE.get(), 8);
+    assert_eq!(
+        AbstractMemorySize::new(9).words(),
+        AbstractMemorySize::new(2)
+    );
+    assert_eq!(
+        AbstractMemorySize::new(8).words(),
+        AbstractMemorySize::new(1)
+    );
+    assert_eq!(
+        AbstractMemorySize::new(0).words(),
+        AbstractMemorySize::new(0)
+    );
+    assert_eq!(
+        words_in(AbstractMemorySize::new(9)),
+        AbstractMemorySize::new(9).words()
+    );
+}
+
+#[test]
+fn saturating_arithmetic() {
+    let near_max = GasUnits::new(u64::max_value() - 1);
+    assert_eq!(
+        near_max.saturating_add(GasUnits::new(5)),
+        GasUnits::new(u64::max_value())
+    );
+    assert_eq!(
+        near_max.saturating_mul(GasPrice::new(2)),
+        GasUnits::new(u64::max_value())
+    );
+    assert_eq!(
+        GasUnits::new(3).saturating_add(GasUnits::new(4)),
+        GasUnits::new(7)
+    );
+    assert_eq!(
+        GasUnits::new(3).saturating_mul(GasUnits::new(4)),
+        GasUnits::new(12)
+    );
+}
+
+#[test]
+fn missing_schedule_entry_is_an_error() {
+    let table = CostTable::new(vec![(Bytecode::Add, 45, 1)]);
+    let size = AbstractMemorySize::new(1);
+    assert_eq!(table.comp_gas(&Bytecode::Add, size), Ok(GasUnits::new(45)));
+    assert_eq!(table.memory_gas(&Bytecode::Add, size), Ok(GasUnits::new(1)));
+
+    let key = InstructionKey::new(&Bytecode::Sub).0;
+    assert_eq!(
+        table.comp_gas(&Bytecode::Sub, size),
+        Err(VMInvariantViolation::GasScheduleError(key))
+    );
+    assert_eq!(
+        table.memory_gas(&Bytecode::Sub, size),
+        Err(VMInvariantViolation::GasScheduleError(key))
+    );
+}
+
+#[test]
+fn cost_table_display() {
+    let table = CostTable::new(vec![
+        (Bytecode::Ret, 28, 1),
+        (Bytecode::Add, 45, 2),
+        (Bytecode::MoveLoc(0), 41, 1),
+    ]);
+    let rendered = table.to_string();
+    assert!(rendered.contains("Add: 45/2\n"));
+    assert!(rendered.contains("Ret: 28/1\n"));
+    assert!(rendered.contains("MoveLoc: 41/1\n"));
+    assert_eq!(rendered.lines().count(), 3);
+}
+
+proptest! {
+    #[test]
+    fn instruction_key_matches_serialization(instr in any::<Bytecode>()) {
+        let mut serialized = vec![];
+        serialize_instruction(&mut serialized, &instr).unwrap();
+        prop_assert_eq!(InstructionKey::new(&instr), InstructionKey(serialized[0]));
+        // Every instruction has an entry in the gas schedule.
+        let cost = static_cost_instr(&instr, AbstractMemorySize::new(1)).unwrap();
+        prop_assert!(cost.instruction_gas.get() > 0);
+    }
+}
@@ -4,4 +4,5 @@
 mod binary_tests;
 mod deserializer_tests;
 mod fixture_tests;
+mod gas_schedule_tests;
 mod number_tests;
@@ -16,7 +16,7 @@ use stdlib::{
         ROTATE_AUTHENTICATION_KEY_TXN_BODY,
     },
 };
-use tiny_keccak::Keccak;
+use transaction_builder::{transaction::script_code_hash, EncodeError};
 use types::{
     access_path::AccessPath,
     account_address::AccountAddress,
@@ -233,38 +233,94 @@ pub fn encode_mint_program(sender: &AccountAddress, amount: u64) -> Program {
     )
 }
 
+/// The stdlib transaction scripts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StdlibScript {
+    PeerToPeer,
+    CreateAccount,
+    Mint,
+    RotateAuthenticationKey,
+}
+
+impl StdlibScript {
+    /// All of the stdlib transaction scripts.
+    pub fn all() -> &'static [StdlibScript] {
+        &[
+            StdlibScript::PeerToPeer,
+            StdlibScript::CreateAccount,
+            StdlibScript::Mint,
+            StdlibScript::RotateAuthenticationKey,
+        ]
+    }
+
+    /// The compiled code of this script.
+    pub fn code(self) -> &'static [u8] {
+        match self {
+            StdlibScript::PeerToPeer => &PEER_TO_PEER_TXN[..],
+            StdlibScript::CreateAccount => &CREATE_ACCOUNT_TXN[..],
+            StdlibScript::Mint => &MINT_TXN[..],
+            StdlibScript::RotateAuthenticationKey => &ROTATE_AUTHENTICATION_KEY_TXN[..],
+        }
+    }
+
+    /// The stdlib script whose compiled code is `code`, if any.
+    pub fn try_from_code(code: &[u8]) -> Option<Self> {
+        Self::all()
+            .iter()
+            .cloned()
+            .find(|script| script.code() == code)
+    }
+}
+
+/// Recover the stdlib script a program runs along with its arguments, e.g. to inspect submitted
+/// transactions. Returns `None` if the program runs any other script or publishes modules.
+pub fn decode_stdlib_program(
+    program: &Program,
+) -> Option<(StdlibScript, Vec<TransactionArgument>)> {
+    if !program.modules().is_empty() {
+        return None;
+    }
+    let script = StdlibScript::try_from_code(program.code())?;
+    Some((script, program.args().to_vec()))
+}
+
 /// Returns a user friendly mnemonic for the transaction type if the transaction is
 /// for a known, white listed, transaction.
 pub fn get_transaction_name(code: &[u8]) -> String {
-    if code == &PEER_TO_PEER_TXN[..] {
-        return "peer_to_peer_transaction".to_string();
-    } else if code == &CREATE_ACCOUNT_TXN[..] {
-        return "create_account_transaction".to_string();
-    } else if code == &MINT_TXN[..] {
-        return "mint_transaction".to_string();
-    } else if code == &ROTATE_AUTHENTICATION_KEY_TXN[..] {
-        return "rotate_authentication_key_transaction".to_string();
+    match StdlibScript::try_from_code(code) {
+        Some(StdlibScript::PeerToPeer) => "peer_to_peer_transaction",
+        Some(StdlibScript::CreateAccount) => "create_account_transaction",
+        Some(StdlibScript::Mint) => "mint_transaction",
+        Some(StdlibScript::RotateAuthenticationKey) => "rotate_authentication_key_transaction",
+        None => "<unknown transaction>",
     }
-    "<unknown transaction>".to_string()
+    .to_string()
 }
 
 pub fn allowing_script_hashes() -> Vec<[u8; SCRIPT_HASH_LENGTH]> {
-    vec![
-        MINT_TXN.clone(),
-        PEER_TO_PEER_TXN.clone(),
-        ROTATE_AUTHENTICATION_KEY_TXN.clone(),
-        CREATE_ACCOUNT_TXN.clone(),
-    ]
-    .into_iter()
-    .map(|s| {
-        let mut hash = [0u8; SCRIPT_HASH_LENGTH];
-        let mut keccak = Keccak::new_sha3_256();
-
-        keccak.update(&s);
-        keccak.finalize(&mut hash);
-        hash
-    })
-    .collect()
+    StdlibScript::all()
+        .iter()
+        .map(|script| script_code_hash(script.code()))
+        .collect()
+}
+
+/// Encode a program running the stdlib transaction script with hash `hash`, for networks that only
+/// accept whitelisted scripts. Fails with `EncodeError::NotAllowListed` if `hash` is not in
+/// `allow_list`, or with `EncodeError::UnknownScript` if it is not the hash of one of the stdlib
+/// transaction scripts.
+pub fn encode_allowlisted_script(
+    hash: [u8; SCRIPT_HASH_LENGTH],
+    allow_list: &[[u8; SCRIPT_HASH_LENGTH]],
+    args: Vec<TransactionArgument>,
+) -> std::result::Result<Program, EncodeError> {
+    if !allow_list.contains(&hash) {
+        return Err(EncodeError::NotAllowListed(hex::encode(hash)));
+    }
+    let script = StdlibScript::all()
+        .iter()
+        .find(|script| script_code_hash(script.code()) == hash)
+        .ok_or_else(|| EncodeError::UnknownScript(hex::encode(hash)))?;
+    Ok(Program::new(script.code().to_vec(), vec![], args))
 }
 
 pub fn default_config() -> VMConfig {
@@ -211,12 +211,15 @@ impl<'alloc> VMModuleCache<'alloc> {
     }
 
     /// Resolve a StructHandle into a StructDef recursively in either the cache or the `fetcher`.
-    pub fn resolve_struct_handle_with_fetcher<F: ModuleFetcher>(
+    /// `resolving` is the stack of struct definitions being resolved, see
+    /// `resolve_struct_def_with_fetcher`.
+    fn resolve_struct_handle_with_fetcher<F: ModuleFetcher>(
         &self,
         module: &LoadedModule,
         idx: StructHandleIndex,
         gas_meter: &GasMeter,
         fetcher: &F,
+        resolving: &mut Vec<(ModuleId, StructDefinitionIndex)>,
     ) -> VMResult<Option<StructDef>> {
         let struct_handle = module.struct_handle_at(idx);
         let struct_name = module.string_at(struct_handle.name);
@@ -227,7 +230,13 @@ impl<'alloc> VMModuleCache<'alloc> {
                     .struct_defs_table
                     .get(struct_name)
                     .ok_or(VMInvariantViolation::LinkerError)?;
-                self.resolve_struct_def_with_fetcher(module, *struct_def_idx, gas_meter, fetcher)
+                self.resolve_struct_def_guarded(
+                    module,
+                    *struct_def_idx,
+                    gas_meter,
+                    fetcher,
+                    resolving,
+                )
             }
             Ok(None) => Ok(Ok(None)),
             Err(errors) => Ok(Err(errors)),
@@ -235,12 +244,15 @@ impl<'alloc> VMModuleCache<'alloc> {
     }
 
     /// Resolve a SignatureToken into a Type recursively in either the cache or the `fetcher`.
-    pub fn resolve_signature_token_with_fetcher<'txn, F: ModuleFetcher>(
+    /// `resolving` is the stack of struct definitions being resolved, see
+    /// `resolve_struct_def_with_fetcher`.
+    fn resolve_signature_token_with_fetcher<'txn, F: ModuleFetcher>(
         &'txn self,
         module: &LoadedModule,
         tok: &SignatureToken,
         gas_meter: &GasMeter,
         fetcher: &F,
+        resolving: &mut Vec<(ModuleId, StructDefinitionIndex)>,
     ) -> VMResult<Option<Type>> {
         match tok {
             SignatureToken::Bool => Ok(Ok(Some(Type::Bool))),
@@ -250,21 +262,21 @@ impl<'alloc> VMModuleCache<'alloc> {
             SignatureToken::Address => Ok(Ok(Some(Type::Address))),
             SignatureToken::TypeParameter(_) => unimplemented!(),
             SignatureToken::Struct(sh_idx, _) => {
-                let struct_def =
-                    try_runtime!(self
-                        .resolve_struct_handle_with_fetcher(module, *sh_idx, gas_meter, fetcher));
+                let struct_def = try_runtime!(self.resolve_struct_handle_with_fetcher(
+                    module, *sh_idx, gas_meter, fetcher, resolving
+                ));
                 Ok(Ok(struct_def.map(Type::Struct)))
             }
             SignatureToken::Reference(sub_tok) => {
-                let inner_ty =
-                    try_runtime!(self
-                        .resolve_signature_token_with_fetcher(module, sub_tok, gas_meter, fetcher));
+                let inner_ty = try_runtime!(self.resolve_signature_token_with_fetcher(
+                    module, sub_tok, gas_meter, fetcher, resolving
+                ));
                 Ok(Ok(inner_ty.map(|t| Type::Reference(Box::new(t)))))
             }
             SignatureToken::MutableReference(sub_tok) => {
-                let inner_ty =
-                    try_runtime!(self
-                        .resolve_signature_token_with_fetcher(module, sub_tok, gas_meter, fetcher));
+                let inner_ty = try_runtime!(self.resolve_signature_token_with_fetcher(
+                    module, sub_tok, gas_meter, fetcher, resolving
+                ));
                 Ok(Ok(inner_ty.map(|t| Type::MutableReference(Box::new(t)))))
             }
         }
@@ -272,33 +284,44 @@ impl<'alloc> VMModuleCache<'alloc> {
 
     /// Resolve a StructDefinition into a StructDef recursively in either the cache or the
     /// `fetcher`.
+    ///
+    /// Struct definitions that (transitively) contain themselves cannot be resolved. The bytecode
+    /// verifier rejects them within a module, but not across modules, so they are detected here
+    /// and reported as a `LinkerError` rather than recursing forever.
     pub fn resolve_struct_def_with_fetcher<'txn, F: ModuleFetcher>(
         &'txn self,
         module: &LoadedModule,
         idx: StructDefinitionIndex,
         gas_meter: &GasMeter,
         fetcher: &F,
+    ) -> VMResult<Option<StructDef>> {
+        self.resolve_struct_def_guarded(module, idx, gas_meter, fetcher, &mut vec![])
+    }
+
+    /// Resolve a StructDefinition into a StructDef, where `resolving` is the stack of struct
+    /// definitions whose resolution is already in progress.
+    fn resolve_struct_def_guarded<'txn, F: ModuleFetcher>(
+        &'txn self,
+        module: &LoadedModule,
+        idx: StructDefinitionIndex,
+        gas_meter: &GasMeter,
+        fetcher: &F,
+        resolving: &mut Vec<(ModuleId, StructDefinitionIndex)>,
     ) -> VMResult<Option<StructDef>> {
         if let Some(def) = module.cached_struct_def_at(idx) {
             return Ok(Ok(Some(def)));
         }
-        let def = {
-            let struct_def = module.struct_def_at(idx);
-            let mut field_types = vec![];
-            for field in module.field_def_range(struct_def.field_count, struct_def.fields) {
-                let ty = try_runtime!(self.resolve_signature_token_with_fetcher(
-                    module,
-                    &module.type_signature_at(field.signature).0,
-                    gas_meter,
-                    fetcher
-                ));
-                if let Some(t) = ty {
-                    field_types.push(t);
-                } else {
-                    return Ok(Ok(None));
-                }
-            }
-            StructDef::new(field_types)
+        let key = (module.self_id(), idx);
+        if resolving.contains(&key) {
+            // The struct definitions are cyclic.
+            return Err(VMInvariantViolation::LinkerError);
+        }
+        resolving.push(key);
+        let field_types = self.resolve_field_types(module, idx, gas_meter, fetcher, resolving);
+        resolving.pop();
+        let def = match try_runtime!(field_types) {
+            Some(field_types) => StructDef::new(field_types),
+            None => return Ok(Ok(None)),
         };
         // If multiple writers write to def at the same time, the last one will win. It's possible
         // to have multiple copies of a struct def floating around, but that probably isn't going
@@ -306,6 +329,34 @@ impl<'alloc> VMModuleCache<'alloc> {
         module.cache_struct_def(idx, def.clone());
         Ok(Ok(Some(def)))
     }
+
+    /// Resolve the types of the fields of a StructDefinition.
+    fn resolve_field_types<F: ModuleFetcher>(
+        &self,
+        module: &LoadedModule,
+        idx: StructDefinitionIndex,
+        gas_meter: &GasMeter,
+        fetcher: &F,
+        resolving: &mut Vec<(ModuleId, StructDefinitionIndex)>,
+    ) -> VMResult<Option<Vec<Type>>> {
+        let struct_def = module.struct_def_at(idx);
+        let mut field_types = vec![];
+        for field in module.field_def_range(struct_def.field_count, struct_def.fields) {
+            let ty = try_runtime!(self.resolve_signature_token_with_fetcher(
+                module,
+                &module.type_signature_at(field.signature).0,
+                gas_meter,
+                fetcher,
+                resolving
+            ));
+            if let Some(t) = ty {
+                field_types.push(t);
+            } else {
+                return Ok(Ok(None));
+            }
+        }
+        Ok(Ok(Some(field_types)))
+    }
 }
 
 impl<'alloc> ModuleCache<'alloc> for VMModuleCache<'alloc> {
@@ -7,7 +7,7 @@ use prometheus::{IntCounter, IntGauge};
 use std::convert::TryFrom;
 use types::{
     transaction::TransactionStatus,
-    vm_error::{VMStatus, VMValidationStatus},
+    vm_error::{ExecutionStatus, VMStatus, VMValidationStatus},
 };
 
 // constants used to create counters
@@ -16,6 +16,8 @@ const TXN_EXECUTION_DISCARD: &str = "txn.execution.discard";
 const TXN_VERIFICATION_SUCCESS: &str = "txn.verification.success";
 const TXN_VERIFICATION_FAIL: &str = "txn.verification.fail";
 const TXN_BLOCK_COUNT: &str = "txn.block.count";
+const TXN_GAS_INTRINSIC: &str = "txn.gas.intrinsic";
+const TXN_GAS_EXECUTION: &str = "txn.gas.execution";
 
 lazy_static::lazy_static! {
     // the main metric (move_vm)
@@ -23,6 +25,8 @@ lazy_static::lazy_static! {
 
     static ref VERIFIED_TRANSACTION: IntCounter = VM_COUNTERS.counter(TXN_VERIFICATION_SUCCESS);
     static ref BLOCK_TRANSACTION_COUNT: IntGauge = VM_COUNTERS.gauge(TXN_BLOCK_COUNT);
+    static ref INTRINSIC_GAS_USED: IntCounter = VM_COUNTERS.counter(TXN_GAS_INTRINSIC);
+    static ref EXECUTION_GAS_USED: IntCounter = VM_COUNTERS.counter(TXN_GAS_EXECUTION);
 }
 
 /// Reports the number of transactions in a block.
@@ -33,6 +37,13 @@ pub fn report_block_count(count: usize) {
     }
 }
 
+/// Reports the gas units used by a transaction, split into the intrinsic portion charged for the
+/// size of the transaction and the portion spent executing it.
+pub fn report_gas_used(intrinsic_gas: u64, execution_gas: u64) {
+    INTRINSIC_GAS_USED.inc_by(intrinsic_gas as i64);
+    EXECUTION_GAS_USED.inc_by(execution_gas as i64);
+}
+
 /// Reports the result of a transaction execution.
 ///
 /// Counters are prefixed with `TXN_EXECUTION_KEEP` or `TXN_EXECUTION_DISCARD`.
@@ -62,6 +73,10 @@ fn inc_counter(prefix: &str, status: &VMStatus) {
             // all serialization error are lumped into one bucket
             VM_COUNTERS.inc(&format!("{}.deserialization", prefix));
         }
+        VMStatus::Execution(ExecutionStatus::Aborted(err_code, _)) => {
+            // aborts are counted by code only, leaving out where they happened
+            VM_COUNTERS.inc(&format!("{}.Aborted({})", prefix, err_code));
+        }
         VMStatus::Execution(status) => {
             // counters for ExecutionStatus are as granular as the enum
             VM_COUNTERS.inc(&format!("{}.{:?}", prefix, status));
@@ -99,6 +99,11 @@ impl<'txn> TransactionDataCache<'txn> {
         }
     }
 
+    /// A cache over the same remote data as this one, without any of this cache's local writes.
+    pub fn fork_remote(&self) -> Self {
+        TransactionDataCache::new(self.data_cache)
+    }
+
     // Retrieve data from the local cache or loads it from the remote cache into the local cache.
     // All operations on the global data are based on this API and they all load the data
     // into the cache.
@@ -278,6 +283,28 @@ impl<'txn> TransactionDataCache<'txn> {
         }
     }
 
+    /// Return the writes made to global resources so far. Unlike `make_write_set` this leaves the
+    /// cache untouched, so it can be called in the middle of a transaction for debugging.
+    pub fn pending_writes(&self) -> VMRuntimeResult<Vec<(AccessPath, WriteOp)>> {
+        self.data_map
+            .iter()
+            .filter(|(_, global_ref)| !global_ref.is_clean())
+            .map(|(key, global_ref)| {
+                let op = if global_ref.is_deleted() {
+                    WriteOp::Deletion
+                } else if let Some(blob) = global_ref.simple_serialize() {
+                    WriteOp::Value(blob)
+                } else {
+                    return Err(VMRuntimeError {
+                        loc: Location::new(),
+                        err: VMErrorKind::ValueSerializerError,
+                    });
+                };
+                Ok((key.clone(), op))
+            })
+            .collect()
+    }
+
     /// Flush out the cache and restart from a clean state
     pub fn clear(&mut self) {
         self.data_map.clear()
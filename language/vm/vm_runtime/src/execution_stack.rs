@@ -42,14 +42,18 @@ where
     pub fn push_call(&mut self, function: FunctionRef<'txn>) -> VMResult<()> {
         let callee_arg_size = function.arg_count();
         let args = self.popn(callee_arg_size as u16)?;
-        self.function_stack.push(Frame::new(function, args));
+        let entry_stack_height = self.stack.len();
+        self.function_stack
+            .push(Frame::new(function, args, Some(entry_stack_height)));
         Ok(Ok(()))
     }
 
     pub fn pop_call(&mut self) -> VMResult<()> {
-        self.function_stack
+        let frame = self
+            .function_stack
             .pop()
             .ok_or(VMInvariantViolation::EmptyCallStack)?;
+        frame.check_return_stack_height(self.stack.len())?;
         Ok(Ok(()))
     }
 
@@ -60,6 +64,19 @@ where
             .ok_or(VMInvariantViolation::EmptyCallStack)?)
     }
 
+    /// The frame `index` calls below the top of the call stack, so `frame_at(0)` is the top frame.
+    pub fn frame_at(
+        &self,
+        index: usize,
+    ) -> Result<&Frame<'txn, FunctionRef<'txn>>, VMInvariantViolation> {
+        Ok(self
+            .function_stack
+            .iter()
+            .rev()
+            .nth(index)
+            .ok_or(VMInvariantViolation::EmptyCallStack)?)
+    }
+
     pub fn top_frame_mut(
         &mut self,
     ) -> Result<&mut Frame<'txn, FunctionRef<'txn>>, VMInvariantViolation> {
@@ -128,6 +145,13 @@ where
         self.function_stack.len()
     }
 
+    /// Drop every frame above `call_stack_height` and every value above `value_stack_height`,
+    /// without the checks a return would make.
+    pub fn unwind(&mut self, call_stack_height: usize, value_stack_height: usize) {
+        self.function_stack.truncate(call_stack_height);
+        self.stack.truncate(value_stack_height);
+    }
+
     pub fn set_stack(&mut self, stack: Vec<Local>) {
         self.stack = stack;
     }
@@ -137,7 +161,7 @@ where
     }
 
     pub fn push_frame(&mut self, func: FunctionRef<'txn>) {
-        self.function_stack.push(Frame::new(func, vec![]));
+        self.function_stack.push(Frame::new(func, vec![], None));
     }
 }
 
@@ -8,8 +8,9 @@ use crate::{
 };
 use std::{fmt, marker::PhantomData, mem::replace};
 use vm::{
+    access::ModuleAccess,
     errors::{Location, VMInvariantViolation, VMResult},
-    file_format::{Bytecode, CodeOffset, LocalIndex},
+    file_format::{Bytecode, CodeOffset, LocalIndex, SignatureToken},
     IndexKind,
 };
 
@@ -17,6 +18,9 @@ pub struct Frame<'txn, F: 'txn> {
     pc: u16,
     locals: Vec<Local>,
     function: F,
+    // Height of the value stack when the frame was entered, after the arguments were popped. This
+    // is `None` for frames that were set up by hand rather than by a call.
+    entry_stack_height: Option<usize>,
     phantom: PhantomData<&'txn F>,
 }
 
@@ -24,12 +28,13 @@ impl<'txn, F> Frame<'txn, F>
 where
     F: FunctionReference<'txn>,
 {
-    pub fn new(function: F, mut args: Vec<Local>) -> Self {
+    pub fn new(function: F, mut args: Vec<Local>, entry_stack_height: Option<usize>) -> Self {
         args.resize(function.local_count(), Local::Invalid);
         Frame {
             pc: 0,
             locals: args,
             function,
+            entry_stack_height,
             phantom: PhantomData,
         }
     }
@@ -82,11 +87,55 @@ where
     pub fn module(&self) -> &'txn LoadedModule {
         self.function.module()
     }
+
+    pub fn function_name(&self) -> &'txn str {
+        self.function.name()
+    }
+
+    pub fn locals(&self) -> &[Local] {
+        &self.locals
+    }
+
+    /// Whether any local of a resource type still holds a value, i.e. a resource would be
+    /// destroyed if the frame returned now. Locals of a generic type are not checked.
+    pub fn has_unused_resources(&self) -> bool {
+        let module = self.module();
+        self.locals
+            .iter()
+            .zip(self.function.local_types())
+            .any(|(local, ty)| match (local, ty) {
+                (Local::Value(_), SignatureToken::Struct(idx, _)) => {
+                    module.struct_handle_at(*idx).kind.is_resource()
+                }
+                _ => false,
+            })
+    }
+
+    /// Check that the value stack holds exactly the values returned by this frame's function on
+    /// top of what was there when the frame was entered.
+    pub fn check_return_stack_height(
+        &self,
+        stack_height: usize,
+    ) -> Result<(), VMInvariantViolation> {
+        if let Some(entry_height) = self.entry_stack_height {
+            let expected_height = entry_height + self.function.return_count();
+            if stack_height != expected_height {
+                return Err(VMInvariantViolation::StackImbalance(
+                    stack_height,
+                    expected_height,
+                ));
+            }
+        }
+        Ok(())
+    }
 }
 
-impl<'txn, F> Into<Location> for &Frame<'txn, F> {
+impl<'txn, F> Into<Location> for &Frame<'txn, F>
+where
+    F: FunctionReference<'txn>,
+{
     fn into(self) -> Location {
-        Location::new()
+        Location::in_function(self.function.name())
     }
 }
 
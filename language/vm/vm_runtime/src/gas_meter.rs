@@ -6,9 +6,25 @@ use crate::{
     code_cache::module_cache::ModuleCache, execution_stack::ExecutionStack,
     loaded_data::function::FunctionReference, value::Local,
 };
+use std::collections::BTreeMap;
 use types::account_address::ADDRESS_LENGTH;
 use vm::{access::ModuleAccess, errors::*, file_format::Bytecode, gas_schedule::*};
 
+/// The kinds of work that gas is charged for. Used to break down the gas used by a transaction.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum GasCategory {
+    /// Executing instructions and native functions.
+    Compute,
+    /// Reading resources from global storage.
+    StorageRead,
+    /// Writing resources to global storage.
+    StorageWrite,
+    /// Emitting events.
+    Event,
+    /// The charge for the size of the transaction, see `GasMeter::charge_transaction_gas`.
+    Intrinsic,
+}
+
 /// Holds the state of the gas meter.
 pub struct GasMeter {
     // The current amount of gas that is left ("unburnt gas") in the gas meter.
@@ -17,6 +33,9 @@ pub struct GasMeter {
     // We need to disable and enable gas metering for both the prologue and epilogue of the Account
     // contract. The VM will then internally unset/set this flag before executing either of them.
     meter_on: bool,
+
+    // The consumed gas, broken down by what it was charged for.
+    usage_by_category: BTreeMap<GasCategory, GasCarrier>,
 }
 
 // NB: A number of the functions/methods in this struct will return a VMResult<T>
@@ -28,9 +47,18 @@ impl GasMeter {
         GasMeter {
             current_gas_left: gas_amount,
             meter_on: true,
+            usage_by_category: BTreeMap::new(),
         }
     }
 
+    /// Put the meter back in the state `GasMeter::new(max_gas)` would create, so that one meter
+    /// can be reused across the transactions of a block.
+    pub fn reset(&mut self, max_gas: GasUnits<GasCarrier>) {
+        self.current_gas_left = max_gas;
+        self.meter_on = true;
+        self.usage_by_category.clear();
+    }
+
     /// Charges additional gas for the transaction based upon the total size (in bytes) of the
     /// submitted transaction. It is important that we charge for the transaction size since a
     /// transaction can contain arbitrary amounts of bytes in the `note` field. We also want to
@@ -46,7 +74,25 @@ impl GasMeter {
         P: ModuleCache<'alloc>,
     {
         let cost = calculate_intrinsic_gas(transaction_size);
-        self.consume_gas(cost, stk)
+        self.consume_gas_for(GasCategory::Intrinsic, cost, stk)
+    }
+
+    /// Get the amount of gas that has been charged by `charge_transaction_gas` so far.
+    ///
+    /// The rest of the consumed gas was spent executing instructions.
+    pub fn intrinsic_gas_charged(&self) -> GasUnits<GasCarrier> {
+        GasUnits::new(
+            self.usage_by_category
+                .get(&GasCategory::Intrinsic)
+                .cloned()
+                .unwrap_or(0),
+        )
+    }
+
+    /// Get the consumed gas, broken down by what it was charged for. Categories nothing was
+    /// charged for are omitted.
+    pub fn usage_by_category(&self) -> BTreeMap<GasCategory, GasCarrier> {
+        self.usage_by_category.clone()
     }
 
     /// Queries the internal state of the gas meter to determine if it has at
@@ -87,7 +133,8 @@ impl GasMeter {
     {
         if self.meter_on {
             let instruction_gas = try_runtime!(self.gas_for_instruction(instr, stk, memory_size));
-            self.consume_gas(instruction_gas, stk)
+            let category = Self::category_of(instr, stk)?;
+            self.consume_gas_for(category, instruction_gas, stk)
         } else {
             Ok(Ok(()))
         }
@@ -137,30 +184,31 @@ impl GasMeter {
             | Bytecode::GetTxnPublicKey
             | Bytecode::GetTxnSenderAddress
             | Bytecode::GetTxnSequenceNumber
+            | Bytecode::GetTxnExpirationTime
             | Bytecode::Ge
             | Bytecode::EmitEvent
             | Bytecode::FreezeRef => {
-                let default_gas = static_cost_instr(instr, AbstractMemorySize::new(1));
+                let default_gas = static_cost_instr(instr, AbstractMemorySize::new(1))?;
                 Self::gas_of(default_gas)
             }
             Bytecode::LdAddr(_) => {
                 let size = AbstractMemorySize::new(ADDRESS_LENGTH as GasCarrier);
-                let default_gas = static_cost_instr(instr, size);
+                let default_gas = static_cost_instr(instr, size)?;
                 Self::gas_of(default_gas)
             }
             Bytecode::LdByteArray(idx) => {
-                let byte_array_ref = stk.top_frame()?.module().byte_array_at(*idx);
+                let byte_array_ref = stk.top_frame()?.module().checked_byte_array_at(*idx)?;
                 let byte_array_len = AbstractMemorySize::new(byte_array_ref.len() as GasCarrier);
                 let byte_array_len = words_in(byte_array_len);
-                let default_gas = static_cost_instr(instr, byte_array_len);
+                let default_gas = static_cost_instr(instr, byte_array_len)?;
                 Self::gas_of(default_gas)
             }
             // We charge by the length of the string being stored on the stack.
             Bytecode::LdStr(idx) => {
-                let string_ref = stk.top_frame()?.module().string_at(*idx);
+                let string_ref = stk.top_frame()?.module().checked_string_at(*idx)?;
                 let str_len = AbstractMemorySize::new(string_ref.len() as GasCarrier);
                 let str_len = words_in(str_len);
-                let default_gas = static_cost_instr(instr, str_len);
+                let default_gas = static_cost_instr(instr, str_len)?;
                 Self::gas_of(default_gas)
             }
             Bytecode::StLoc(_) => {
@@ -168,14 +216,14 @@ impl GasMeter {
                 let local = stk.peek()?;
                 // Get the size of the local
                 let size = local.size();
-                let default_gas = static_cost_instr(instr, size);
+                let default_gas = static_cost_instr(instr, size)?;
                 Self::gas_of(default_gas)
             }
             // Note that a moveLoc incurs a copy overhead
             Bytecode::CopyLoc(local_idx) | Bytecode::MoveLoc(local_idx) => {
                 let local = stk.top_frame()?.get_local(*local_idx)?;
                 let size = local.size();
-                let default_gas = static_cost_instr(instr, size);
+                let default_gas = static_cost_instr(instr, size)?;
                 Self::gas_of(default_gas)
             }
             // A return does not affect the value stack at all, and simply pops the call stack
@@ -183,7 +231,7 @@ impl GasMeter {
             // value stack.  Because of this, the cost of the instruction is not dependent upon the
             // size of the value being returned.
             Bytecode::Ret => {
-                let default_gas = static_cost_instr(instr, AbstractMemorySize::new(1));
+                let default_gas = static_cost_instr(instr, AbstractMemorySize::new(1))?;
                 Self::gas_of(default_gas)
             }
             Bytecode::Call(call_idx, _) => {
@@ -196,13 +244,13 @@ impl GasMeter {
                     GasUnits::new(0) // This will be costed at the call site/by the native function
                 } else {
                     let call_size = AbstractMemorySize::new(function_ref.arg_count() as GasCarrier);
-                    let call_gas = static_cost_instr(instr, call_size);
+                    let call_gas = static_cost_instr(instr, call_size)?;
                     Self::gas_of(call_gas)
                 }
             }
             Bytecode::Unpack(_, _) => {
                 let size = stk.peek()?.size();
-                Self::gas_of(static_cost_instr(instr, size))
+                Self::gas_of(static_cost_instr(instr, size)?)
             }
             Bytecode::Pack(struct_idx, _) => {
                 let struct_def = &stk.top_frame()?.module().struct_def_at(*struct_idx);
@@ -211,7 +259,7 @@ impl GasMeter {
                 // the struct.
                 let arg_count = AbstractMemorySize::new(u64::from(struct_def.field_count));
                 let total_size = arg_count.add(*STRUCT_SIZE);
-                let new_gas = static_cost_instr(instr, total_size);
+                let new_gas = static_cost_instr(instr, total_size)?;
                 Self::gas_of(new_gas)
             }
             Bytecode::WriteRef => {
@@ -221,7 +269,7 @@ impl GasMeter {
                 let ref_val = stk.peek()?;
                 // Get the size of this value and charge accordingly.
                 let size = write_val.size();
-                let mut default_gas = static_cost_instr(instr, size);
+                let mut default_gas = static_cost_instr(instr, size)?;
                 // Determine if the reference is global. If so charge for any expansion of global
                 // memory along with the write operation that will be incurred.
                 if let Local::GlobalRef(_) = ref_val {
@@ -246,18 +294,18 @@ impl GasMeter {
                 // from global memory that is performed by a BorrowGlobal operation. After this,
                 // all ReadRefs will be reading from local cache and we don't need to distinguish.
                 let size = stk.peek()?.size();
-                let default_gas = static_cost_instr(instr, size);
+                let default_gas = static_cost_instr(instr, size)?;
                 Self::gas_of(default_gas)
             }
             | Bytecode::BorrowLoc(_)
             | Bytecode::BorrowField(_) => {
-                let default_gas = static_cost_instr(instr, AbstractMemorySize::new(1));
+                let default_gas = static_cost_instr(instr, AbstractMemorySize::new(1))?;
                 Self::gas_of(default_gas)
             }
-            Bytecode::CreateAccount => Self::gas_of(static_cost_instr(instr, *DEFAULT_ACCOUNT_SIZE)),
+            Bytecode::CreateAccount => Self::gas_of(static_cost_instr(instr, *DEFAULT_ACCOUNT_SIZE)?),
             // Releasing a reference is not dependent on the size of the underlying data
             Bytecode::ReleaseRef => {
-                Self::gas_of(static_cost_instr(instr, AbstractMemorySize::new(1)))
+                Self::gas_of(static_cost_instr(instr, AbstractMemorySize::new(1))?)
             }
             // Note that we charge twice for these operations; once at the start of
             // `execute_single_instruction` we charge once with size 1. This then covers the cost
@@ -283,7 +331,7 @@ impl GasMeter {
                 } else {
                     AbstractMemorySize::new(0) // We already charged for size 1
                 };
-                Self::gas_of(static_cost_instr(instr, mem_size))
+                Self::gas_of(static_cost_instr(instr, mem_size)?)
             }
         };
         Ok(Ok(instruction_reqs))
@@ -297,13 +345,29 @@ impl GasMeter {
         self.current_gas_left
     }
 
-    /// Consume the amount of gas given by `gas_amount`. If there is not enough gas
-    /// left in the internal state, an `OutOfGasError` is returned.
+    /// Consume the amount of gas given by `gas_amount` for computation. If there is not enough
+    /// gas left in the internal state, an `OutOfGasError` is returned.
     pub fn consume_gas<'alloc, 'txn, P>(
         &mut self,
         gas_amount: GasUnits<GasCarrier>,
         stk: &ExecutionStack<'alloc, 'txn, P>,
     ) -> VMResult<()>
+    where
+        'alloc: 'txn,
+        P: ModuleCache<'alloc>,
+    {
+        self.consume_gas_for(GasCategory::Compute, gas_amount, stk)
+    }
+
+    /// Consume the amount of gas given by `gas_amount`, attributing it to `category`. If there is
+    /// not enough gas left in the internal state, the remaining gas is attributed to `category`
+    /// and an `OutOfGasError` is returned.
+    pub fn consume_gas_for<'alloc, 'txn, P>(
+        &mut self,
+        category: GasCategory,
+        gas_amount: GasUnits<GasCarrier>,
+        stk: &ExecutionStack<'alloc, 'txn, P>,
+    ) -> VMResult<()>
     where
         'alloc: 'txn,
         P: ModuleCache<'alloc>,
@@ -316,8 +380,10 @@ impl GasMeter {
             .app(&gas_amount, |curr_gas, gas_amt| curr_gas >= gas_amt)
         {
             self.current_gas_left = self.current_gas_left.sub(gas_amount);
+            *self.usage_by_category.entry(category).or_insert(0) += gas_amount.get();
             Ok(Ok(()))
         } else {
+            *self.usage_by_category.entry(category).or_insert(0) += self.current_gas_left.get();
             // Zero out the internal gas state
             self.current_gas_left = GasUnits::new(0);
             let location = stk.location().unwrap_or_default();
@@ -328,6 +394,30 @@ impl GasMeter {
         }
     }
 
+    /// The category the gas charged for executing `instr` is attributed to.
+    fn category_of<'alloc, 'txn, P>(
+        instr: &Bytecode,
+        stk: &ExecutionStack<'alloc, 'txn, P>,
+    ) -> Result<GasCategory, VMInvariantViolation>
+    where
+        'alloc: 'txn,
+        P: ModuleCache<'alloc>,
+    {
+        Ok(match instr {
+            Bytecode::BorrowGlobal(_, _) | Bytecode::Exists(_, _) | Bytecode::MoveFrom(_, _) => {
+                GasCategory::StorageRead
+            }
+            Bytecode::MoveToSender(_, _) | Bytecode::CreateAccount => GasCategory::StorageWrite,
+            // Writing through a reference to global storage is a storage write.
+            Bytecode::WriteRef => match stk.peek()? {
+                Local::GlobalRef(_) => GasCategory::StorageWrite,
+                _ => GasCategory::Compute,
+            },
+            Bytecode::EmitEvent => GasCategory::Event,
+            _ => GasCategory::Compute,
+        })
+    }
+
     /// Take a GasCost from our gas schedule and convert it to a total gas charge in `GasUnits`.
     ///
     /// This is used internally for converting from a `GasCost` which is a triple of numbers
@@ -6,7 +6,10 @@ use crate::loaded_data::loaded_module::LoadedModule;
 use bytecode_verifier::VerifiedModule;
 use vm::{
     access::ModuleAccess,
-    file_format::{Bytecode, CodeUnit, FunctionDefinitionIndex, FunctionHandle, FunctionSignature},
+    file_format::{
+        Bytecode, CodeUnit, FunctionDefinitionIndex, FunctionHandle, FunctionSignature,
+        LocalsSignatureIndex, SignatureToken,
+    },
     internals::ModuleIndex,
 };
 
@@ -38,6 +41,9 @@ pub trait FunctionReference<'txn>: Sized + Clone {
 
     /// Returns the signature of the function.
     fn signature(&self) -> &'txn FunctionSignature;
+
+    /// Return the types of the function's locals, arguments included. Native functions have none.
+    fn local_types(&self) -> &'txn [SignatureToken];
 }
 
 /// Resolved form of a function handle
@@ -77,7 +83,7 @@ impl<'txn> FunctionReference<'txn> for FunctionRef<'txn> {
     }
 
     fn return_count(&self) -> usize {
-        self.def.local_count
+        self.def.return_count
     }
 
     fn is_native(&self) -> bool {
@@ -91,6 +97,14 @@ impl<'txn> FunctionReference<'txn> for FunctionRef<'txn> {
     fn signature(&self) -> &'txn FunctionSignature {
         self.module.function_signature_at(self.handle.signature)
     }
+
+    fn local_types(&self) -> &'txn [SignatureToken] {
+        if self.is_native() {
+            &[]
+        } else {
+            &self.module.locals_signature_at(self.def.locals).0
+        }
+    }
 }
 
 /// Resolved form of a function definition
@@ -101,6 +115,7 @@ pub struct FunctionDef {
     pub return_count: usize,
     pub code: Vec<Bytecode>,
     pub flags: u8,
+    pub locals: LocalsSignatureIndex,
 }
 
 impl FunctionDef {
@@ -114,6 +129,7 @@ impl FunctionDef {
         FunctionDef {
             code,
             flags,
+            locals: definition.code.locals,
             arg_count: function_sig.arg_types.len(),
             return_count: function_sig.return_types.len(),
             // Local count for native function is omitted
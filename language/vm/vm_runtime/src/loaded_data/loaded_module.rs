@@ -2,17 +2,22 @@
 // SPDX-License-Identifier: Apache-2.0
 //! Loaded representation for Move modules.
 
-use crate::loaded_data::{function::FunctionDef, struct_def::StructDef};
+use crate::{
+    bounded_fetch,
+    loaded_data::{function::FunctionDef, struct_def::StructDef},
+};
 use bytecode_verifier::VerifiedModule;
 use std::{collections::HashMap, sync::RwLock};
+use types::{account_address::AccountAddress, byte_array::ByteArray};
 use vm::{
     access::ModuleAccess,
     errors::VMInvariantViolation,
     file_format::{
-        CompiledModule, FieldDefinitionIndex, FunctionDefinitionIndex, MemberCount,
-        StructDefinitionIndex, TableIndex,
+        AddressPoolIndex, ByteArrayPoolIndex, CompiledModule, FieldDefinitionIndex,
+        FunctionDefinitionIndex, MemberCount, StringPoolIndex, StructDefinitionIndex, TableIndex,
     },
     internals::ModuleIndex,
+    IndexKind,
 };
 
 /// Defines a loaded module in the memory. Currently we just store module itself with a bunch of
@@ -137,6 +142,37 @@ impl LoadedModule {
             .cloned()
             .ok_or(VMInvariantViolation::LinkerError)
     }
+
+    /// Like `address_at`, but fails rather than panics if `idx` is out of bounds, which only
+    /// unverified code can cause.
+    pub fn checked_address_at(
+        &self,
+        idx: AddressPoolIndex,
+    ) -> Result<&AccountAddress, VMInvariantViolation> {
+        bounded_fetch(
+            self.address_pool(),
+            idx.into_index(),
+            IndexKind::AddressPool,
+        )
+    }
+
+    /// Like `string_at`, but fails rather than panics if `idx` is out of bounds.
+    pub fn checked_string_at(&self, idx: StringPoolIndex) -> Result<&str, VMInvariantViolation> {
+        bounded_fetch(self.string_pool(), idx.into_index(), IndexKind::StringPool)
+            .map(String::as_str)
+    }
+
+    /// Like `byte_array_at`, but fails rather than panics if `idx` is out of bounds.
+    pub fn checked_byte_array_at(
+        &self,
+        idx: ByteArrayPoolIndex,
+    ) -> Result<&ByteArray, VMInvariantViolation> {
+        bounded_fetch(
+            self.byte_array_pool(),
+            idx.into_index(),
+            IndexKind::ByteArrayPool,
+        )
+    }
 }
 
 // Compile-time test to ensure that this struct stays thread-safe.
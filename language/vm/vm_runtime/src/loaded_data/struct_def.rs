@@ -17,7 +17,18 @@ pub struct StructDef(Arc<StructDefInner>);
 impl StructDef {
     /// Constructs a new [`StructDef`]
     pub fn new(field_definitions: Vec<Type>) -> Self {
-        Self(Arc::new(StructDefInner { field_definitions }))
+        let depth = 1 + field_definitions
+            .iter()
+            .map(|ty| match ty {
+                Type::Struct(struct_def) => struct_def.depth(),
+                _ => 1,
+            })
+            .max()
+            .unwrap_or(0);
+        Self(Arc::new(StructDefInner {
+            field_definitions,
+            depth,
+        }))
     }
 
     /// Get type declaration for each field in the struct.
@@ -25,12 +36,20 @@ impl StructDef {
     pub fn field_definitions(&self) -> &[Type] {
         &self.0.field_definitions
     }
+
+    /// The nesting depth of every value of this struct: one more than the deepest field, where
+    /// values of primitive types have depth 1.
+    #[inline]
+    pub fn depth(&self) -> usize {
+        self.0.depth
+    }
 }
 
 // Do not implement Clone for this -- the outer StructDef should be Arc'd.
 #[derive(Debug, Eq, PartialEq)]
 struct StructDefInner {
     field_definitions: Vec<Type>,
+    depth: usize,
 }
 
 /// This isn't used by any normal code at the moment, but is used by the fuzzer to serialize types
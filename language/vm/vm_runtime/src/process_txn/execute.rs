@@ -99,11 +99,8 @@ where
                 publish_modules.push((module_id, raw_bytes));
             }
 
-            // Set up main.
-            txn_executor.setup_main_args(args);
-
             // Run main.
-            match txn_executor.execute_function_impl(main) {
+            match txn_executor.execute_main(main, args) {
                 Ok(Ok(_)) => txn_executor.transaction_cleanup(publish_modules),
                 Ok(Err(err)) => {
                     warn!("[VM] User error running script: {:?}", err);
@@ -4,6 +4,7 @@
 
 use crate::{
     code_cache::module_cache::{ModuleCache, VMModuleCache},
+    counters::report_gas_used,
     data_cache::{RemoteCache, TransactionDataCache},
     execution_stack::ExecutionStack,
     gas_meter::GasMeter,
@@ -16,6 +17,7 @@ use crate::{
 };
 use bytecode_verifier::{VerifiedModule, VerifiedScript};
 use move_ir_natives::dispatch::{dispatch_native_call, NativeReturnType};
+use std::collections::{BTreeMap, HashSet};
 use types::{
     access_path::AccessPath,
     account_address::AccountAddress,
@@ -25,13 +27,13 @@ use types::{
     language_storage::ModuleId,
     transaction::{TransactionArgument, TransactionOutput, TransactionStatus},
     vm_error::{ExecutionStatus, VMStatus},
-    write_set::WriteSet,
+    write_set::{WriteOp, WriteSet},
 };
 use vm::{
     access::ModuleAccess,
     errors::*,
-    file_format::{Bytecode, CodeOffset, CompiledScript, StructDefinitionIndex},
-    gas_schedule::{AbstractMemorySize, GasAlgebra, GasUnits},
+    file_format::{Bytecode, CodeOffset, CompiledScript, LocalIndex, StructDefinitionIndex},
+    gas_schedule::{AbstractMemorySize, GasAlgebra, GasCarrier, GasUnits},
     transaction_metadata::TransactionMetadata,
 };
 use vm_cache_map::Arena;
@@ -55,6 +57,10 @@ const EPILOGUE_NAME: &str = "epilogue";
 const CREATE_ACCOUNT_NAME: &str = "make";
 const ACCOUNT_STRUCT_NAME: &str = "T";
 
+/// The default for the deepest value a `Pack` may construct. Bounding the depth keeps the recursive
+/// operations on values, such as equality and serialization, from overflowing the native stack.
+pub const DEFAULT_MAX_VALUE_DEPTH: usize = 256;
+
 fn make_access_path(
     module: &impl ModuleAccess,
     idx: StructDefinitionIndex,
@@ -64,6 +70,54 @@ fn make_access_path(
     create_access_path(&address, struct_tag)
 }
 
+/// The native function that prints its arguments to the executor's debug output. It is handled by
+/// the executor itself rather than dispatched to the natives, since it needs the executor's state.
+const DEBUG_MODULE: &str = "Debug";
+const DEBUG_PRINT_FUNCTION: &str = "print";
+
+/// Flat gas charged for a call to the debug print native.
+const DEBUG_PRINT_COST: u64 = 10;
+
+/// A location in code to pause execution at: the module, the name of the function and the offset
+/// of the instruction in that function.
+pub type Breakpoint = (ModuleId, String, CodeOffset);
+
+/// A snapshot of the interpreter taken when execution paused at a breakpoint.
+#[derive(Debug)]
+pub struct InterpreterState {
+    pub module: ModuleId,
+    pub function_name: String,
+    /// Offset of the instruction about to be executed.
+    pub pc: CodeOffset,
+    /// Locals of the paused frame.
+    pub locals: Vec<Local>,
+    /// The whole value stack.
+    pub stack: Vec<Local>,
+}
+
+/// The result of running code under `debug_function` or `continue_execution`.
+#[derive(Debug)]
+pub enum ExecutionOutcome {
+    /// The function ran to completion.
+    Completed,
+    /// Execution paused before executing the instruction at a breakpoint.
+    DebugBreak(InterpreterState),
+}
+
+/// An executed instruction: the path of the function (`address::module::function`), the offset of
+/// the instruction in that function, and the instruction itself.
+pub type TraceEntry = (String, CodeOffset, Bytecode);
+
+/// Bookkeeping for a function being run under the debugger.
+struct DebugSession {
+    /// Call stack height to run down to.
+    beginning_height: usize,
+    /// Set by `execute_block` when a breakpoint is hit.
+    break_pc: Option<CodeOffset>,
+    /// The breakpoint execution is resuming from, which must not fire again straight away.
+    resume_pc: Option<CodeOffset>,
+}
+
 /// A struct that executes one single transaction.
 /// 'alloc is the lifetime for the code cache, which is the argument type P here. Hence the P should
 /// live as long as alloc.
@@ -72,6 +126,23 @@ fn make_access_path(
 /// `txn_data` contains the information of this transaction, such as sender, sequence number, etc.
 /// `event_data` is the vector that stores all events emitted during execution.
 /// `data_view` is the scratchpad for the local writes emitted by this transaction.
+/// `forced_create_account_address`, when set, is the address every `CreateAccount` creates an
+/// account at, regardless of the operand on the stack. Used by cost synthesis and deterministic
+/// tests.
+/// `max_value_depth` is the deepest value a `Pack` may construct.
+/// `expose_txn_public_key` controls whether `GetTxnPublicKey` may read the sender's public key.
+/// `breakpoints` are the instructions `debug_function` pauses at, and `debug_session` tracks the
+/// function currently running under it.
+/// `in_memory_trace`, when set, records every instruction executed.
+/// `debug_output`, when set, collects the lines printed by the `Debug.print` native.
+/// `instruction_budget`, when set, is the number of instructions left to execute, counted whether
+/// or not gas is being metered.
+/// `end_of_code_is_return` makes running off the end of the code return control instead of being an
+/// invariant violation, so that tests and cost synthesis can run bare instruction sequences.
+/// `strict_resource_checks` makes `Ret` fail if a resource would be destroyed along with the frame.
+/// `gas_by_function`, when set, accumulates the gas used by each function from its call to its
+/// return, callees included. `call_gas_checkpoints` holds the gas left when each frame on the call
+/// stack was entered.
 pub struct TransactionExecutor<'alloc, 'txn, P>
 where
     'alloc: 'txn,
@@ -86,6 +157,18 @@ where
     txn_data: TransactionMetadata,
     event_data: Vec<ContractEvent>,
     data_view: TransactionDataCache<'txn>,
+    forced_create_account_address: Option<AccountAddress>,
+    max_value_depth: usize,
+    expose_txn_public_key: bool,
+    breakpoints: HashSet<Breakpoint>,
+    debug_session: Option<DebugSession>,
+    in_memory_trace: Option<Vec<TraceEntry>>,
+    debug_output: Option<Vec<String>>,
+    instruction_budget: Option<u64>,
+    strict_resource_checks: bool,
+    end_of_code_is_return: bool,
+    gas_by_function: Option<BTreeMap<(ModuleId, String), GasCarrier>>,
+    call_gas_checkpoints: Vec<GasCarrier>,
 }
 
 impl<'alloc, 'txn, P> TransactionExecutor<'alloc, 'txn, P>
@@ -108,6 +191,18 @@ where
             txn_data,
             event_data: Vec::new(),
             data_view: TransactionDataCache::new(data_cache),
+            forced_create_account_address: None,
+            max_value_depth: DEFAULT_MAX_VALUE_DEPTH,
+            expose_txn_public_key: true,
+            breakpoints: HashSet::new(),
+            debug_session: None,
+            in_memory_trace: None,
+            debug_output: None,
+            instruction_budget: None,
+            strict_resource_checks: false,
+            end_of_code_is_return: false,
+            gas_by_function: None,
+            call_gas_checkpoints: vec![],
         }
     }
 
@@ -116,6 +211,156 @@ where
         &self.execution_stack.module_cache
     }
 
+    /// Force every subsequent `CreateAccount` to create the account at `addr` instead of the
+    /// address popped from the stack. Passing `None` restores the default behavior.
+    pub fn set_forced_create_account_address(&mut self, addr: Option<AccountAddress>) {
+        self.forced_create_account_address = addr;
+    }
+
+    /// Set the deepest value a `Pack` may construct. Defaults to `DEFAULT_MAX_VALUE_DEPTH`.
+    pub fn set_max_value_depth(&mut self, max_value_depth: usize) {
+        self.max_value_depth = max_value_depth;
+    }
+
+    /// Set whether `GetTxnPublicKey` may read the sender's public key. When it may not, the
+    /// instruction fails with `VMErrorKind::TxnFieldUnavailable`. Defaults to `true`.
+    pub fn set_expose_txn_public_key(&mut self, expose_txn_public_key: bool) {
+        self.expose_txn_public_key = expose_txn_public_key;
+    }
+
+    /// Set the breakpoints to pause at when running under `debug_function`. Breakpoints are
+    /// ignored by all other entry points, as well as by the functions the VM calls on its own
+    /// while debugging, such as the account constructor run by `CreateAccount`.
+    pub fn set_breakpoints(&mut self, breakpoints: HashSet<Breakpoint>) {
+        self.breakpoints = breakpoints;
+    }
+
+    /// Limit execution to `budget` more instructions. Once they are used up the next instruction
+    /// fails with `VMErrorKind::ExecutionLimitReached`, even when gas metering is disabled.
+    /// Passing `None` removes the limit.
+    pub fn set_instruction_budget(&mut self, budget: Option<u64>) {
+        self.instruction_budget = budget;
+    }
+
+    /// Start recording every executed instruction in memory. See `take_trace`.
+    pub fn enable_in_memory_trace(&mut self) {
+        if self.in_memory_trace.is_none() {
+            self.in_memory_trace = Some(vec![]);
+        }
+    }
+
+    /// Return the instructions recorded since tracing was enabled or the trace was last taken, and
+    /// clear them. Recording carries on afterwards.
+    pub fn take_trace(&mut self) -> Vec<TraceEntry> {
+        match &mut self.in_memory_trace {
+            Some(trace) => std::mem::replace(trace, vec![]),
+            None => vec![],
+        }
+    }
+
+    /// Set whether `Ret` checks that no local of a resource type still holds a value, failing with
+    /// `VMErrorKind::UnsafeRetUnusedResources` if one does. The bytecode verifier already rules
+    /// this out, so this only matters for unverified code. Defaults to `false`.
+    pub fn set_strict_resource_checks(&mut self, strict_resource_checks: bool) {
+        self.strict_resource_checks = strict_resource_checks;
+    }
+
+    /// Set whether running off the end of the code returns the offset past the last instruction,
+    /// rather than failing with `VMInvariantViolation::ProgramCounterOverflow`. Only meant for
+    /// running instruction sequences that do not end in a return, e.g. in tests and cost synthesis.
+    /// Defaults to `false`.
+    pub fn set_end_of_code_is_return(&mut self, end_of_code_is_return: bool) {
+        self.end_of_code_is_return = end_of_code_is_return;
+    }
+
+    /// Start attributing gas to the functions that use it. See `take_gas_by_function`.
+    pub fn enable_gas_by_function(&mut self) {
+        if self.gas_by_function.is_none() {
+            self.gas_by_function = Some(BTreeMap::new());
+        }
+    }
+
+    /// Return the gas used by each function that returned since attribution was enabled or last
+    /// taken, and clear it. A function's gas includes that of its callees, and accumulates over
+    /// every call to it.
+    pub fn take_gas_by_function(&mut self) -> BTreeMap<(ModuleId, String), GasCarrier> {
+        match &mut self.gas_by_function {
+            Some(gas_by_function) => std::mem::replace(gas_by_function, BTreeMap::new()),
+            None => BTreeMap::new(),
+        }
+    }
+
+    /// Push `function` onto the call stack, remembering the gas left at that point if gas is being
+    /// attributed to functions.
+    fn push_call(&mut self, function: FunctionRef<'txn>) -> VMResult<()> {
+        try_runtime!(self.execution_stack.push_call(function));
+        if self.gas_by_function.is_some() {
+            // Drop the checkpoints of frames that were unwound without returning.
+            let height = self.execution_stack.call_stack_height();
+            self.call_gas_checkpoints.truncate(height - 1);
+            self.call_gas_checkpoints
+                .push(self.gas_meter.remaining_gas().get());
+        }
+        Ok(Ok(()))
+    }
+
+    /// Pop the top frame off the call stack, attributing the gas used since it was pushed to its
+    /// function if gas is being attributed to functions.
+    fn pop_call(&mut self) -> VMResult<()> {
+        if let Some(gas_by_function) = &mut self.gas_by_function {
+            let height = self.execution_stack.call_stack_height();
+            if self.call_gas_checkpoints.len() == height {
+                let gas_at_call = self.call_gas_checkpoints.pop().unwrap_or_default();
+                let gas_used = gas_at_call.saturating_sub(self.gas_meter.remaining_gas().get());
+                let frame = self.execution_stack.top_frame()?;
+                let key = (frame.module().self_id(), frame.function_name().to_string());
+                *gas_by_function.entry(key).or_insert(0) += gas_used;
+            }
+        }
+        self.execution_stack.pop_call()
+    }
+
+    /// Start collecting the output of the `Debug.print` native. Without this, printing is a no-op
+    /// (that is still charged for).
+    pub fn enable_debug_output(&mut self) {
+        if self.debug_output.is_none() {
+            self.debug_output = Some(vec![]);
+        }
+    }
+
+    /// Return the lines printed since debug output was enabled or last taken, and clear them.
+    pub fn take_debug_output(&mut self) -> Vec<String> {
+        match &mut self.debug_output {
+            Some(output) => std::mem::replace(output, vec![]),
+            None => vec![],
+        }
+    }
+
+    /// Pop the `arg_count` arguments of a call to `Debug.print` and print each of them on its own
+    /// line of the debug output. Nothing reaches chain state.
+    fn debug_print(&mut self, arg_count: usize) -> VMResult<()> {
+        try_runtime!(self
+            .gas_meter
+            .consume_gas(GasUnits::new(DEBUG_PRINT_COST), &self.execution_stack));
+        let args = self.execution_stack.popn(arg_count as u16)?;
+        if let Some(output) = &mut self.debug_output {
+            output.extend(args.iter().map(|arg| arg.to_string()));
+        }
+        Ok(Ok(()))
+    }
+
+    /// The path, as in "address::module::function", of the function running in the top frame.
+    fn top_function_path(&self) -> Result<String, VMInvariantViolation> {
+        let frame = self.execution_stack.top_frame()?;
+        let module_id = frame.module().self_id();
+        Ok(format!(
+            "{}::{}::{}",
+            module_id.address(),
+            module_id.name(),
+            frame.function_name()
+        ))
+    }
+
     /// Perform a binary operation to two values at the top of the stack.
     fn binop<F, T>(&mut self, f: F) -> VMResult<()>
     where
@@ -164,6 +409,25 @@ where
     ) -> VMResult<CodeOffset> {
         let mut pc = beginning_offset;
         for instruction in &code[beginning_offset as usize..] {
+            if self.check_breakpoint(pc)? {
+                return Ok(Ok(pc));
+            }
+            if self.in_memory_trace.is_some() {
+                let function_path = self.top_function_path()?;
+                if let Some(trace) = &mut self.in_memory_trace {
+                    trace.push((function_path, pc, instruction.clone()));
+                }
+            }
+            if let Some(budget) = &mut self.instruction_budget {
+                if *budget == 0 {
+                    return Ok(Err(VMRuntimeError {
+                        loc: self.execution_stack.location()?,
+                        err: VMErrorKind::ExecutionLimitReached,
+                    }));
+                }
+                *budget -= 1;
+            }
+
             // FIXME: Once we add in memory ops, we will need to pass in the current memory size to
             // this function.
             try_runtime!(self.gas_meter.calculate_and_consume(
@@ -177,7 +441,15 @@ where
                     self.execution_stack.pop()?;
                 }
                 Bytecode::Ret => {
-                    try_runtime!(self.execution_stack.pop_call());
+                    if self.strict_resource_checks
+                        && self.execution_stack.top_frame()?.has_unused_resources()
+                    {
+                        return Ok(Err(VMRuntimeError {
+                            loc: self.execution_stack.location()?.at_offset(pc),
+                            err: VMErrorKind::UnsafeRetUnusedResources,
+                        }));
+                    }
+                    try_runtime!(self.pop_call());
                     if self.execution_stack.is_call_stack_empty() {
                         return Ok(Ok(0));
                     } else {
@@ -201,18 +473,18 @@ where
                 }
                 Bytecode::LdAddr(idx) => {
                     let top_frame = self.execution_stack.top_frame()?;
-                    let addr_ref = top_frame.module().address_at(idx);
+                    let addr_ref = top_frame.module().checked_address_at(idx)?;
                     self.execution_stack.push(Local::address(*addr_ref));
                 }
                 Bytecode::LdStr(idx) => {
                     let top_frame = self.execution_stack.top_frame()?;
-                    let string_ref = top_frame.module().string_at(idx);
+                    let string_ref = top_frame.module().checked_string_at(idx)?;
                     self.execution_stack
                         .push(Local::string(string_ref.to_string()));
                 }
                 Bytecode::LdByteArray(idx) => {
                     let top_frame = self.execution_stack.top_frame()?;
-                    let byte_array = top_frame.module().byte_array_at(idx);
+                    let byte_array = top_frame.module().checked_byte_array_at(idx)?;
                     self.execution_stack
                         .push(Local::bytearray(byte_array.clone()));
                 }
@@ -248,7 +520,12 @@ where
                         .resolve_function_ref(self_module, idx))
                     .ok_or(VMInvariantViolation::LinkerError)?;
 
-                    if callee_function_ref.is_native() {
+                    if callee_function_ref.is_native()
+                        && callee_function_ref.module().name() == DEBUG_MODULE
+                        && callee_function_ref.name() == DEBUG_PRINT_FUNCTION
+                    {
+                        try_runtime!(self.debug_print(callee_function_ref.arg_count()));
+                    } else if callee_function_ref.is_native() {
                         let module_name: &str = callee_function_ref.module().name();
                         let function_name: &str = callee_function_ref.name();
                         let native_return = dispatch_native_call(
@@ -280,7 +557,7 @@ where
                         }
                     } else {
                         self.execution_stack.top_frame_mut()?.jump(pc);
-                        try_runtime!(self.execution_stack.push_call(callee_function_ref));
+                        try_runtime!(self.push_call(callee_function_ref));
                         // Call stack is reconstructed, the next instruction to execute will be the
                         // first instruction of the callee function. Thus we should break here to
                         // restart the instruction sequence from there.
@@ -330,6 +607,23 @@ where
                 Bytecode::Pack(sd_idx, _) => {
                     let self_module = self.execution_stack.top_frame()?.module();
                     let struct_def = self_module.struct_def_at(sd_idx);
+                    // Every value of a struct type has the same depth, so it is checked against
+                    // the resolved definition instead of the fields being packed.
+                    match try_runtime!(self.execution_stack.module_cache.resolve_struct_def(
+                        self_module,
+                        sd_idx,
+                        &self.gas_meter
+                    )) {
+                        Some(resolved_def) => {
+                            if resolved_def.depth() > self.max_value_depth {
+                                return Ok(Err(VMRuntimeError {
+                                    loc: self.execution_stack.location()?,
+                                    err: VMErrorKind::ValueDepthExceeded,
+                                }));
+                            }
+                        }
+                        None => return Err(VMInvariantViolation::LinkerError),
+                    }
                     let args = self
                         .execution_stack
                         .popn(struct_def.field_count)?
@@ -423,7 +717,7 @@ where
                 Bytecode::Abort => {
                     let error_code = try_runtime!(self.execution_stack.pop_as::<u64>());
                     return Ok(Err(VMRuntimeError {
-                        loc: self.execution_stack.location()?,
+                        loc: self.execution_stack.location()?.at_offset(pc),
                         err: VMErrorKind::Aborted(error_code),
                     }));
                 }
@@ -453,11 +747,21 @@ where
                     self.execution_stack
                         .push(Local::u64(self.txn_data.sequence_number()));
                 }
+                Bytecode::GetTxnExpirationTime => {
+                    self.execution_stack
+                        .push(Local::u64(self.txn_data.expiration_time()));
+                }
                 Bytecode::GetTxnSenderAddress => {
                     self.execution_stack
                         .push(Local::address(self.txn_data.sender()));
                 }
                 Bytecode::GetTxnPublicKey => {
+                    if !self.expose_txn_public_key {
+                        return Ok(Err(VMRuntimeError {
+                            loc: self.execution_stack.location()?,
+                            err: VMErrorKind::TxnFieldUnavailable,
+                        }));
+                    }
                     self.execution_stack.push(Local::bytearray(ByteArray::new(
                         self.txn_data.public_key().to_slice().to_vec(),
                     )));
@@ -554,7 +858,10 @@ where
                     }
                 }
                 Bytecode::CreateAccount => {
+                    // The operand is always popped so the stack stays balanced, even when the
+                    // address is being overridden.
                     let addr = try_runtime!(self.execution_stack.pop_as::<AccountAddress>());
+                    let addr = self.forced_create_account_address.unwrap_or(addr);
                     try_runtime!(self.create_account(addr));
                 }
                 Bytecode::FreezeRef => {
@@ -590,7 +897,7 @@ where
             pc += 1;
         }
 
-        if cfg!(test) || cfg!(feature = "instruction_synthesis") {
+        if self.end_of_code_is_return {
             // In order to test the behavior of an instruction stream, hitting end of the code
             // should report no error so that we can check the locals.
             Ok(Ok(code.len() as CodeOffset))
@@ -599,16 +906,28 @@ where
         }
     }
 
-    /// Convert the transaction arguments into move values and push them to the top of the stack.
-    pub(crate) fn setup_main_args(&mut self, args: Vec<TransactionArgument>) {
+    /// Execute the script `main`, converting the transaction arguments into move values to pass
+    /// as its arguments.
+    pub(crate) fn execute_main(
+        &mut self,
+        main: FunctionRef<'txn>,
+        args: Vec<TransactionArgument>,
+    ) -> VMResult<()> {
+        self.execute_main_with_values(main, convert_txn_args(args))
+    }
+
+    /// Execute the script `main` with arguments that are already move values. This is for callers
+    /// that don't hold the arguments as `TransactionArgument`s and would otherwise have to convert
+    /// them back and forth.
+    pub fn execute_main_with_values(
+        &mut self,
+        main: FunctionRef<'txn>,
+        args: Vec<Local>,
+    ) -> VMResult<()> {
         for arg in args.into_iter() {
-            self.execution_stack.push(match arg {
-                TransactionArgument::U64(i) => Local::u64(i),
-                TransactionArgument::Address(a) => Local::address(a),
-                TransactionArgument::ByteArray(b) => Local::bytearray(b),
-                TransactionArgument::String(s) => Local::string(s),
-            });
+            self.execution_stack.push(arg);
         }
+        self.execute_function_impl(main)
     }
 
     /// Create an account on the blockchain by calling into `CREATE_ACCOUNT_NAME` function stored
@@ -715,13 +1034,47 @@ where
 
     /// Execute a function given a FunctionRef.
     pub(crate) fn execute_function_impl(&mut self, func: FunctionRef<'txn>) -> VMResult<()> {
-        // We charge an intrinsic amount of gas based upon the size of the transaction submitted
-        // (in raw bytes).
-        try_runtime!(self
-            .gas_meter
-            .charge_transaction_gas(self.txn_data.transaction_size, &self.execution_stack));
+        try_runtime!(self.charge_intrinsic_gas());
+        self.execute_call(func)
+    }
+
+    /// Charge the intrinsic gas of the transaction, which is based upon the size of the transaction
+    /// submitted (in raw bytes).
+    fn charge_intrinsic_gas(&mut self) -> VMResult<()> {
+        self.gas_meter
+            .charge_transaction_gas(self.txn_data.transaction_size, &self.execution_stack)
+    }
+
+    /// Look up `function_name` in `module` and push `args` onto the value stack, ready for calling
+    /// the function.
+    fn prepare_call(
+        &mut self,
+        module: &ModuleId,
+        function_name: &str,
+        args: Vec<Local>,
+    ) -> VMResult<FunctionRef<'txn>> {
+        let func = try_runtime!(self.resolve_function(module, function_name));
+
+        for arg in args.into_iter() {
+            self.execution_stack.push(arg);
+        }
+        Ok(Ok(func))
+    }
+
+    /// Call `func` with the arguments on top of the value stack and run it to completion, without
+    /// charging for the transaction itself. Breakpoints only pause code run by the debugger
+    /// directly, so any debug session is set aside for the duration of the call.
+    fn execute_call(&mut self, func: FunctionRef<'txn>) -> VMResult<()> {
+        let debug_session = self.debug_session.take();
+        let result = self.run_call(func);
+        self.debug_session = debug_session;
+        result
+    }
+
+    /// Run `func` to completion, see `execute_call`.
+    fn run_call(&mut self, func: FunctionRef<'txn>) -> VMResult<()> {
         let beginning_height = self.execution_stack.call_stack_height();
-        try_runtime!(self.execution_stack.push_call(func));
+        try_runtime!(self.push_call(func));
         // We always start execution from the first instruction.
         let mut pc = 0;
 
@@ -741,6 +1094,116 @@ where
         Ok(Ok(()))
     }
 
+    /// Execute a function, pausing before any instruction that matches one of the breakpoints.
+    /// On `ExecutionOutcome::DebugBreak` the call stack is left as is and execution can be
+    /// resumed with `continue_execution`.
+    pub fn debug_function(
+        &mut self,
+        module: &ModuleId,
+        function_name: &str,
+        args: Vec<Local>,
+    ) -> VMResult<ExecutionOutcome> {
+        let func = try_runtime!(self.prepare_call(module, function_name, args));
+        try_runtime!(self.charge_intrinsic_gas());
+        let beginning_height = self.execution_stack.call_stack_height();
+        try_runtime!(self.push_call(func));
+        self.debug_session = Some(DebugSession {
+            beginning_height,
+            break_pc: None,
+            resume_pc: None,
+        });
+        self.run_debug_session(0)
+    }
+
+    /// Resume a function paused at a breakpoint. If execution is not paused there is nothing to
+    /// resume and `ExecutionOutcome::Completed` is returned.
+    pub fn continue_execution(&mut self) -> VMResult<ExecutionOutcome> {
+        let pc = match self
+            .debug_session
+            .as_ref()
+            .and_then(|session| session.resume_pc)
+        {
+            Some(pc) => pc,
+            None => return Ok(Ok(ExecutionOutcome::Completed)),
+        };
+        self.run_debug_session(pc)
+    }
+
+    /// Run the function of the current debug session from `pc` in the top frame, until it either
+    /// returns or hits a breakpoint.
+    fn run_debug_session(&mut self, mut pc: CodeOffset) -> VMResult<ExecutionOutcome> {
+        let beginning_height = match &self.debug_session {
+            Some(session) => session.beginning_height,
+            None => return Ok(Ok(ExecutionOutcome::Completed)),
+        };
+        while self.execution_stack.call_stack_height() != beginning_height {
+            let code = self.execution_stack.top_frame()?.code_definition();
+            pc = match self.execute_block(code, pc) {
+                Ok(Ok(pc)) => pc,
+                Ok(Err(err)) => {
+                    self.debug_session = None;
+                    return Ok(Err(err));
+                }
+                Err(err) => {
+                    self.debug_session = None;
+                    return Err(err);
+                }
+            };
+
+            if let Some(session) = &mut self.debug_session {
+                if let Some(break_pc) = session.break_pc.take() {
+                    session.resume_pc = Some(break_pc);
+                    let frame = self.execution_stack.top_frame()?;
+                    return Ok(Ok(ExecutionOutcome::DebugBreak(InterpreterState {
+                        module: frame.module().self_id(),
+                        function_name: frame.function_name().to_string(),
+                        pc: break_pc,
+                        locals: frame.locals().to_vec(),
+                        stack: self.execution_stack.get_value_stack().clone(),
+                    })));
+                }
+            }
+        }
+        self.debug_session = None;
+        Ok(Ok(ExecutionOutcome::Completed))
+    }
+
+    /// Read a copy of local `local_index` of the frame `frame_index` calls below the top of the
+    /// call stack, e.g. while paused at a breakpoint. The local itself is left in place.
+    pub fn read_local(
+        &self,
+        frame_index: usize,
+        local_index: LocalIndex,
+    ) -> Result<Local, VMInvariantViolation> {
+        let frame = self.execution_stack.frame_at(frame_index)?;
+        Ok(frame.get_local(local_index)?.clone())
+    }
+
+    /// Check whether execution should pause before the instruction at `pc` in the top frame,
+    /// recording the break in the debug session if so.
+    fn check_breakpoint(&mut self, pc: CodeOffset) -> Result<bool, VMInvariantViolation> {
+        let resuming = match &mut self.debug_session {
+            Some(session) => session.resume_pc.take() == Some(pc),
+            None => return Ok(false),
+        };
+        if resuming || self.breakpoints.is_empty() {
+            return Ok(false);
+        }
+        let frame = self.execution_stack.top_frame()?;
+        let breakpoint = (
+            frame.module().self_id(),
+            frame.function_name().to_string(),
+            pc,
+        );
+        if !self.breakpoints.contains(&breakpoint) {
+            return Ok(false);
+        }
+        if let Some(session) = &mut self.debug_session {
+            session.break_pc = Some(pc);
+        }
+        Ok(true)
+    }
+
     /// Execute a function.
     /// `module` is an identifier for the name the module is stored in. `function_name` is the name
     /// of the function. If such function is found, the VM will execute this function with arguments
@@ -752,6 +1215,140 @@ where
         function_name: &str,
         args: Vec<Local>,
     ) -> VMResult<()> {
+        let func = try_runtime!(self.prepare_call(module, function_name, args));
+        self.execute_function_impl(func)
+    }
+
+    /// Execute a function in isolation from the rest of the transaction. The function reads the
+    /// remote data only, and its writes, events and return values are all discarded afterwards.
+    /// When `abort_collection` is given, an abort is recorded there along with the path of the
+    /// function that aborted, and the call succeeds so that the caller can carry on; otherwise it
+    /// is returned as an error. Gas used by the function is still charged, but the transaction
+    /// itself is not charged for again.
+    pub fn execute_sandboxed(
+        &mut self,
+        module: &ModuleId,
+        function_name: &str,
+        args: Vec<Local>,
+        abort_collection: Option<&mut Vec<(String, u64)>>,
+    ) -> VMResult<()> {
+        let call_stack_height = self.execution_stack.call_stack_height();
+        let value_stack_height = self.execution_stack.get_value_stack().len();
+        let sandbox_view = self.data_view.fork_remote();
+        let data_view = std::mem::replace(&mut self.data_view, sandbox_view);
+        let event_data = std::mem::replace(&mut self.event_data, vec![]);
+
+        let result = match self.prepare_call(module, function_name, args) {
+            Ok(Ok(func)) => self.execute_call(func),
+            Ok(Err(err)) => Ok(Err(err)),
+            Err(err) => Err(err),
+        };
+        let result = match (result, abort_collection) {
+            (
+                Ok(Err(VMRuntimeError {
+                    err: VMErrorKind::Aborted(code),
+                    ..
+                })),
+                Some(aborts),
+            ) => self.top_function_path().map(|path| {
+                aborts.push((path, code));
+                Ok(())
+            }),
+            (result, _) => result,
+        };
+
+        self.execution_stack
+            .unwind(call_stack_height, value_stack_height);
+        self.data_view = data_view;
+        self.event_data = event_data;
+        result
+    }
+
+    /// Execute a function under a gas meter of its own holding `budget`, e.g. for a query that
+    /// should not draw on the transaction's gas. The transaction's gas meter is left untouched and
+    /// nothing is charged for the transaction itself. Returns the gas the function used. If the
+    /// function fails, the call and value stacks are unwound to where they were before the call.
+    pub fn execute_function_with_budget(
+        &mut self,
+        module: &ModuleId,
+        function_name: &str,
+        args: Vec<Local>,
+        budget: GasUnits<GasCarrier>,
+    ) -> VMResult<GasUnits<GasCarrier>> {
+        let call_stack_height = self.execution_stack.call_stack_height();
+        let value_stack_height = self.execution_stack.get_value_stack().len();
+        let func = try_runtime!(self.prepare_call(module, function_name, args));
+
+        let gas_meter = std::mem::replace(&mut self.gas_meter, GasMeter::new(budget));
+        let result = self.execute_call(func);
+        let budget_meter = std::mem::replace(&mut self.gas_meter, gas_meter);
+        match result {
+            Ok(Ok(())) => Ok(Ok(budget.sub(budget_meter.remaining_gas()))),
+            Ok(Err(err)) => {
+                self.execution_stack
+                    .unwind(call_stack_height, value_stack_height);
+                Ok(Err(err))
+            }
+            Err(err) => {
+                self.execution_stack
+                    .unwind(call_stack_height, value_stack_height);
+                Err(err)
+            }
+        }
+    }
+
+    /// Execute a function and collect its return values.
+    /// Unlike `execute_function`, the return values are popped off the value stack rather than
+    /// left on it. They are returned in declaration order, i.e. the last one was on top of the
+    /// stack. The value stack must otherwise be back to the height it had before the call.
+    pub fn execute_function_returning_values(
+        &mut self,
+        module: &ModuleId,
+        function_name: &str,
+        args: Vec<Local>,
+    ) -> VMResult<Vec<Local>> {
+        let func = try_runtime!(self.resolve_function(module, function_name));
+        let return_count = func.return_count();
+        let entry_stack_height = self.execution_stack.get_value_stack().len();
+
+        for arg in args.into_iter() {
+            self.execution_stack.push(arg);
+        }
+        try_runtime!(self.execute_function_impl(func));
+
+        let expected_height = entry_stack_height + return_count;
+        let stack_height = self.execution_stack.get_value_stack().len();
+        if stack_height != expected_height {
+            return Err(VMInvariantViolation::StackImbalance(
+                stack_height,
+                expected_height,
+            ));
+        }
+        let mut values = (0..return_count)
+            .map(|_| self.execution_stack.pop())
+            .collect::<Result<Vec<_>, _>>()?;
+        values.reverse();
+        Ok(Ok(values))
+    }
+
+    /// Load each of the modules `ids` into the module cache ahead of execution, so that running
+    /// code from them later does not have to fetch them. Fails with a linker error if any of them
+    /// does not exist.
+    pub fn preload_modules(&self, ids: &[ModuleId]) -> VMResult<()> {
+        for id in ids {
+            if try_runtime!(self.execution_stack.module_cache.get_loaded_module(id)).is_none() {
+                return Err(VMInvariantViolation::LinkerError);
+            }
+        }
+        Ok(Ok(()))
+    }
+
+    /// Look up the function named `function_name` in `module`.
+    fn resolve_function(
+        &self,
+        module: &ModuleId,
+        function_name: &str,
+    ) -> VMResult<FunctionRef<'txn>> {
         let loaded_module =
             match try_runtime!(self.execution_stack.module_cache.get_loaded_module(module)) {
                 Some(module) => module,
@@ -761,13 +1358,13 @@ where
             .function_defs_table
             .get(function_name)
             .ok_or(VMInvariantViolation::LinkerError)?;
-        let func = FunctionRef::new(loaded_module, *func_idx);
-
-        for arg in args.into_iter() {
-            self.execution_stack.push(arg);
-        }
+        Ok(Ok(FunctionRef::new(loaded_module, *func_idx)))
+    }
 
-        self.execute_function_impl(func)
+    /// Get the writes this transaction has made to global storage so far, without finalizing
+    /// them. See `TransactionDataCache::pending_writes`.
+    pub fn pending_writes(&self) -> VMRuntimeResult<Vec<(AccessPath, WriteOp)>> {
+        self.data_view.pending_writes()
     }
 
     /// Get the value on the top of the value stack.
@@ -784,12 +1381,13 @@ where
     ) -> VMRuntimeResult<TransactionOutput> {
         // This should only be used for bookkeeping. The gas is already deducted from the sender's
         // account in the account module's epilogue.
-        let gas: u64 = self
+        let gas_used = self
             .txn_data
             .max_gas_amount
-            .sub(self.gas_meter.remaining_gas())
-            .mul(self.txn_data.gas_unit_price)
-            .get();
+            .sub(self.gas_meter.remaining_gas());
+        let intrinsic_gas = self.gas_meter.intrinsic_gas_charged();
+        report_gas_used(intrinsic_gas.get(), gas_used.sub(intrinsic_gas).get());
+        let gas: u64 = gas_used.saturating_mul(self.txn_data.gas_unit_price).get();
         let write_set = self.data_view.make_write_set(to_be_published_modules)?;
 
         Ok(TransactionOutput::new(
@@ -807,6 +1405,18 @@ where
     }
 }
 
+/// Convert the transaction arguments into move values.
+fn convert_txn_args(args: Vec<TransactionArgument>) -> Vec<Local> {
+    args.into_iter()
+        .map(|arg| match arg {
+            TransactionArgument::U64(i) => Local::u64(i),
+            TransactionArgument::Address(a) => Local::address(a),
+            TransactionArgument::ByteArray(b) => Local::bytearray(b),
+            TransactionArgument::String(s) => Local::string(s),
+        })
+        .collect()
+}
+
 #[inline]
 fn error_output(err: impl Into<VMStatus>) -> TransactionOutput {
     // Since this transaction will be discarded, no writeset will be included.
@@ -841,6 +1451,18 @@ pub fn execute_function(
         txn_data: txn_metadata,
         event_data: Vec::new(),
         data_view: TransactionDataCache::new(data_cache),
+        forced_create_account_address: None,
+        max_value_depth: DEFAULT_MAX_VALUE_DEPTH,
+        expose_txn_public_key: true,
+        breakpoints: HashSet::new(),
+        debug_session: None,
+        in_memory_trace: None,
+        debug_output: None,
+        instruction_budget: None,
+        strict_resource_checks: false,
+        end_of_code_is_return: false,
+        gas_by_function: None,
+        call_gas_checkpoints: vec![],
     };
     vm.execute_function_impl(entry_func)
 }
@@ -692,3 +692,58 @@ fn test_dependency_fails_verification() {
             if module_id.address() == &AccountAddress::default() && module_id.name() == "Test"
     );
 }
+
+#[test]
+fn test_cyclic_struct_resolution() {
+    // module M { struct S { f: V#Self.S } }. The verifier rejects this, but the same cycle can be
+    // built across modules, which the verifier does not check.
+    let module = CompiledModuleMut {
+        module_handles: vec![ModuleHandle {
+            address: AddressPoolIndex::new(0),
+            name: StringPoolIndex::new(0),
+        }],
+        struct_handles: vec![StructHandle {
+            module: ModuleHandleIndex::new(0),
+            name: StringPoolIndex::new(1),
+            kind: Kind::Copyable,
+            kind_constraints: vec![],
+        }],
+        struct_defs: vec![StructDefinition {
+            struct_handle: StructHandleIndex::new(0),
+            field_count: 1,
+            fields: FieldDefinitionIndex::new(0),
+        }],
+        field_defs: vec![FieldDefinition {
+            struct_: StructHandleIndex::new(0),
+            name: StringPoolIndex::new(2),
+            signature: TypeSignatureIndex::new(0),
+        }],
+        type_signatures: vec![TypeSignature(SignatureToken::Struct(
+            StructHandleIndex::new(0),
+            vec![],
+        ))],
+        string_pool: vec!["M".to_string(), "S".to_string(), "f".to_string()],
+        address_pool: vec![AccountAddress::default()],
+        ..CompiledModuleMut::default()
+    }
+    .freeze()
+    .expect("test module should satisfy bounds checker");
+    let module = VerifiedModule::bypass_verifier_DANGEROUS_FOR_TESTING_ONLY(module);
+
+    let allocator = Arena::new();
+    let vm_cache = VMModuleCache::new(&allocator);
+    vm_cache.cache_module(module);
+    let module_id = ModuleId::new(AccountAddress::default(), "M".to_string());
+    let module_ref = vm_cache
+        .get_loaded_module(&module_id)
+        .unwrap()
+        .unwrap()
+        .unwrap();
+    let gas = GasMeter::new(GasUnits::new(100_000_000));
+    assert_eq!(
+        vm_cache
+            .resolve_struct_def(module_ref, StructDefinitionIndex::new(0), &gas)
+            .unwrap_err(),
+        VMInvariantViolation::LinkerError
+    );
+}
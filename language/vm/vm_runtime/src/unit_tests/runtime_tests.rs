@@ -3,20 +3,32 @@
 
 use super::*;
 use crate::{
-    code_cache::module_cache::VMModuleCache, txn_executor::TransactionExecutor, value::Local,
+    code_cache::{
+        module_adapter::{FakeFetcher, ModuleFetcher},
+        module_cache::{BlockModuleCache, VMModuleCache},
+    },
+    gas_meter::GasCategory,
+    txn_executor::TransactionExecutor,
+    value::Local,
 };
+use assert_matches::assert_matches;
 use bytecode_verifier::{VerifiedModule, VerifiedScript};
-use std::collections::HashMap;
-use types::{access_path::AccessPath, account_address::AccountAddress, byte_array::ByteArray};
+use std::{cell::Cell, collections::HashMap, rc::Rc};
+use types::{
+    access_path::AccessPath, account_address::AccountAddress, byte_array::ByteArray,
+    vm_error::AbortLocation,
+};
 use vm::{
     file_format::{
-        AddressPoolIndex, Bytecode, CodeUnit, CompiledModuleMut, CompiledScript, CompiledScriptMut,
-        FunctionDefinition, FunctionHandle, FunctionHandleIndex, FunctionSignature,
-        FunctionSignatureIndex, LocalsSignature, LocalsSignatureIndex, ModuleHandle,
-        ModuleHandleIndex, SignatureToken, StringPoolIndex, NO_TYPE_ACTUALS,
+        AddressPoolIndex, ByteArrayPoolIndex, Bytecode, CodeUnit, CompiledModule,
+        CompiledModuleMut, CompiledScript, CompiledScriptMut, FunctionDefinition, FunctionHandle,
+        FunctionHandleIndex, FunctionSignature, FunctionSignatureIndex, LocalsSignature,
+        LocalsSignatureIndex, ModuleHandle, ModuleHandleIndex, SignatureToken, StringPoolIndex,
+        NO_TYPE_ACTUALS,
     },
-    gas_schedule::{AbstractMemorySize, GasAlgebra, GasPrice, GasUnits},
+    gas_schedule::{calculate_intrinsic_gas, AbstractMemorySize, GasAlgebra, GasPrice, GasUnits},
     transaction_metadata::TransactionMetadata,
+    IndexKind,
 };
 use vm_cache_map::Arena;
 
@@ -77,6 +89,55 @@ fn fake_script() -> VerifiedScript {
     VerifiedScript::new(compiled_script).expect("test script should satisfy bytecode verifier")
 }
 
+/// Allocate the fake script in `allocator` and return its main function, e.g. to push a frame for
+/// running single instructions in.
+fn fake_script_entry(allocator: &Arena<LoadedModule>) -> FunctionRef<'_> {
+    let loaded_main = allocator.alloc(LoadedModule::new(fake_script().into_module()));
+    FunctionRef::new(loaded_main, CompiledScript::MAIN_INDEX)
+}
+
+/// A module with a single function, `incr`, that stores its argument plus one in a local and
+/// returns it.
+const INCR_MODULE: &str = "
+        module M {
+            public incr(x: u64): u64 {
+                let y: u64;
+                y = move(x) + 1;
+                return move(y);
+            }
+        }
+        ";
+
+/// Compile `modules`, the source of one or more modules, without the standard library.
+fn compile_modules(modules: &str) -> Vec<CompiledModule> {
+    let code = format!(
+        "modules:\n{}\nscript:\nmain() {{\n    return;\n}}\n",
+        modules
+    );
+    let compiler = compiler::Compiler {
+        code: &code,
+        skip_stdlib_deps: true,
+        ..compiler::Compiler::default()
+    };
+    compiler
+        .into_compiled_program()
+        .expect("Failed to compile program")
+        .modules
+}
+
+/// Compile `module`, the source of a single module, and cache it in a new module cache allocated
+/// in `allocator`. Returns the id of the module along with the cache.
+fn setup_module<'alloc>(
+    module: &str,
+    allocator: &'alloc Arena<LoadedModule>,
+) -> (ModuleId, VMModuleCache<'alloc>) {
+    let module = compile_modules(module).remove(0);
+    let module_id = module.self_id();
+    let module_cache = VMModuleCache::new(allocator);
+    module_cache.cache_module(VerifiedModule::new(module).unwrap());
+    (module_id, module_cache)
+}
+
 fn test_simple_instruction_impl<'alloc, 'txn>(
     vm: &mut TransactionExecutor<'alloc, 'txn, VMModuleCache<'alloc>>,
     instr: Bytecode,
@@ -87,6 +148,7 @@ fn test_simple_instruction_impl<'alloc, 'txn>(
     expected_offset: u16,
 ) -> VMResult<()> {
     let code = vec![instr];
+    vm.set_end_of_code_is_return(true);
     vm.execution_stack
         .top_frame_mut()?
         .set_with_states(0, local_before);
@@ -719,6 +781,7 @@ fn test_transaction_info() {
             max_gas_amount: GasUnits::new(100_000_009),
             gas_unit_price: GasPrice::new(5),
             transaction_size: AbstractMemorySize::new(100),
+            expiration_time: 42,
         }
     };
     let data_cache = FakeDataCache::new();
@@ -755,4 +818,975 @@ fn test_transaction_info() {
         vec![],
         1,
     );
+
+    test_simple_instruction(
+        &mut vm,
+        Bytecode::GetTxnExpirationTime,
+        vec![],
+        vec![Local::u64(42)],
+        vec![],
+        vec![],
+        1,
+    );
+}
+
+#[test]
+fn test_ret_stack_imbalance() {
+    let module = fake_module_with_calls(vec![
+        // () -> (), no local
+        (
+            vec![],
+            FunctionSignature {
+                arg_types: vec![],
+                return_types: vec![],
+                kind_constraints: vec![],
+            },
+        ),
+        // () -> (Int), no local
+        (
+            vec![],
+            FunctionSignature {
+                arg_types: vec![],
+                return_types: vec![SignatureToken::U64],
+                kind_constraints: vec![],
+            },
+        ),
+    ]);
+
+    let mod_id = module.self_id();
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new_from_module(module, &allocator).unwrap();
+    let (unit_func, u64_func) = {
+        let fake_mod_entry = module_cache
+            .get_loaded_module(&mod_id)
+            .unwrap()
+            .unwrap()
+            .unwrap();
+        let resolve = |idx| {
+            module_cache
+                .resolve_function_ref(fake_mod_entry, FunctionHandleIndex::new(idx))
+                .unwrap()
+                .unwrap()
+                .unwrap()
+        };
+        (resolve(0), resolve(1))
+    };
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(module_cache, &data_cache, TransactionMetadata::default());
+
+    // A function returning one value that leaves exactly one value on the stack is fine.
+    vm.execution_stack.push_call(u64_func).unwrap().unwrap();
+    vm.execution_stack.push(Local::u64(1));
+    assert_eq!(vm.execute_block(&[Bytecode::Ret], 0).unwrap().unwrap(), 0);
+    vm.execution_stack.pop().unwrap();
+
+    // A function returning nothing that leaves an extra value on the stack is caught.
+    vm.execution_stack.push_call(unit_func).unwrap().unwrap();
+    vm.execution_stack.push(Local::u64(1));
+    assert_eq!(
+        vm.execute_block(&[Bytecode::Ret], 0).unwrap_err(),
+        VMInvariantViolation::StackImbalance(1, 0)
+    );
+}
+
+#[test]
+fn test_forced_create_account_address() {
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new(&allocator);
+    for module in stdlib::stdlib_modules() {
+        module_cache.cache_module(module.clone());
+    }
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+    vm.execution_stack.push_frame(fake_script_entry(&allocator));
+
+    let operand = AccountAddress::new([1; 32]);
+    let forced = AccountAddress::new([2; 32]);
+    vm.set_forced_create_account_address(Some(forced));
+    vm.set_end_of_code_is_return(true);
+
+    vm.execution_stack.set_stack(vec![Local::address(operand)]);
+    let offset = vm
+        .execute_block(&[Bytecode::CreateAccount], 0)
+        .unwrap()
+        .unwrap();
+    assert_eq!(offset, 1);
+    // The operand must still be consumed.
+    assert!(vm.execution_stack.get_value_stack().is_empty());
+
+    let write_set = vm.data_view.make_write_set(vec![]).unwrap();
+    let account_path = account_config::account_resource_path();
+    assert!(write_set
+        .iter()
+        .any(|(ap, _)| ap.address == forced && ap.path == account_path));
+    assert!(write_set.iter().all(|(ap, _)| ap.address != operand));
+}
+
+#[test]
+fn test_intrinsic_gas_charged() {
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new(&allocator);
+    let entry_func = fake_script_entry(&allocator);
+
+    let txn_info = TransactionMetadata {
+        transaction_size: AbstractMemorySize::new(1_000),
+        ..TransactionMetadata::default()
+    };
+    let max_gas_amount = txn_info.max_gas_amount();
+    let data_cache = FakeDataCache::new();
+    let mut vm = TransactionExecutor::new(module_cache, &data_cache, txn_info);
+
+    vm.execute_function_impl(entry_func).unwrap().unwrap();
+
+    let intrinsic_gas = vm.gas_meter.intrinsic_gas_charged();
+    assert_eq!(
+        intrinsic_gas,
+        calculate_intrinsic_gas(AbstractMemorySize::new(1_000))
+    );
+    // Executing the `Ret` of the script is not intrinsic gas.
+    let gas_used = max_gas_amount.sub(vm.gas_meter.remaining_gas());
+    assert!(gas_used.get() > intrinsic_gas.get());
+}
+
+#[test]
+fn test_abort_location() {
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new(&allocator);
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(module_cache, &data_cache, TransactionMetadata::default());
+    vm.execution_stack.push_frame(fake_script_entry(&allocator));
+
+    let code = vec![
+        Bytecode::LdConst(0),
+        Bytecode::Pop,
+        Bytecode::LdConst(7),
+        Bytecode::Abort,
+    ];
+    let result = vm.execute_block(code.as_slice(), 0);
+    // The location reaches the status reported for the transaction. "hello" is the name of the
+    // fake script's main function.
+    assert_eq!(
+        vm_status_of_result(&result),
+        VMStatus::Execution(ExecutionStatus::Aborted(
+            7,
+            Some(AbortLocation {
+                function: "hello".to_string(),
+                code_offset: 3,
+            })
+        ))
+    );
+}
+
+#[test]
+fn test_pending_writes() {
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new(&allocator);
+    for module in stdlib::stdlib_modules() {
+        module_cache.cache_module(module.clone());
+    }
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+    assert!(vm.pending_writes().unwrap().is_empty());
+
+    let addr = AccountAddress::new([3; 32]);
+    vm.create_account(addr).unwrap().unwrap();
+
+    let account_path = AccessPath::new(addr, account_config::account_resource_path());
+    let pending_writes = vm.pending_writes().unwrap();
+    assert!(pending_writes.iter().any(|(ap, op)| match op {
+        WriteOp::Value(_) => *ap == account_path,
+        WriteOp::Deletion => false,
+    }));
+
+    // Looking at the pending writes must not consume them.
+    let write_set = vm.data_view.make_write_set(vec![]).unwrap();
+    assert!(write_set.iter().any(|(ap, _)| *ap == account_path));
+}
+
+#[test]
+fn test_execute_function_returning_values() {
+    let code = "
+        module M {
+            public two(x: u64): u64 * u64 {
+                return (copy(x), move(x) + 1);
+            }
+        }
+        ";
+    let allocator = Arena::new();
+    let (module_id, module_cache) = setup_module(code, &allocator);
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+
+    let values = vm
+        .execute_function_returning_values(&module_id, "two", vec![Local::u64(5)])
+        .unwrap()
+        .unwrap();
+    assert_eq!(values.len(), 2);
+    assert!(values[0].clone().equals(Local::u64(5)).unwrap());
+    assert!(values[1].clone().equals(Local::u64(6)).unwrap());
+    assert!(vm.execution_stack.get_value_stack().is_empty());
+}
+
+#[test]
+fn test_max_value_depth() {
+    let code = "
+        module M {
+            struct A { x: u64 }
+            struct B { a: V#Self.A }
+            public make(): V#Self.B {
+                return B { a: A { x: 1 } };
+            }
+        }
+        ";
+    let allocator = Arena::new();
+    let (module_id, module_cache) = setup_module(code, &allocator);
+    let data_cache = FakeDataCache::new();
+
+    // B { a: A { x } } has depth 3.
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+    vm.set_max_value_depth(3);
+    vm.execute_function(&module_id, "make", vec![])
+        .unwrap()
+        .unwrap();
+
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+    vm.set_max_value_depth(2);
+    match vm.execute_function(&module_id, "make", vec![]).unwrap() {
+        Err(VMRuntimeError { err, .. }) => assert_eq!(err, VMErrorKind::ValueDepthExceeded),
+        Ok(()) => panic!("packing B should exceed the maximum value depth"),
+    }
+}
+
+#[test]
+fn test_gas_usage_by_category() {
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new(&allocator);
+    for module in stdlib::stdlib_modules() {
+        module_cache.cache_module(module.clone());
+    }
+    let data_cache = FakeDataCache::new();
+    let txn_info = TransactionMetadata::default();
+    let max_gas_amount = txn_info.max_gas_amount();
+    let mut vm = TransactionExecutor::new(&module_cache, &data_cache, txn_info);
+    vm.execution_stack.push_frame(fake_script_entry(&allocator));
+
+    // Creating an account runs the account module's constructor and writes the new resource.
+    vm.set_end_of_code_is_return(true);
+    vm.execution_stack
+        .set_stack(vec![Local::address(AccountAddress::new([4; 32]))]);
+    vm.execute_block(&[Bytecode::CreateAccount], 0)
+        .unwrap()
+        .unwrap();
+
+    let usage = vm.gas_meter.usage_by_category();
+    assert!(usage[&GasCategory::Compute] > 0);
+    assert!(usage[&GasCategory::StorageWrite] > 0);
+    assert!(!usage.contains_key(&GasCategory::Event));
+    // Every unit of gas used is attributed to exactly one category.
+    let gas_used = max_gas_amount.sub(vm.gas_meter.remaining_gas()).get();
+    assert_eq!(usage.values().sum::<u64>(), gas_used);
+}
+
+#[test]
+fn test_execute_main_with_values() {
+    let code = "
+        main(x: u64, b: bytearray) {
+            assert(move(x) == 5, 42);
+            return;
+        }
+        ";
+    let compiler = compiler::Compiler {
+        code,
+        skip_stdlib_deps: true,
+        ..compiler::Compiler::default()
+    };
+    let script = compiler
+        .into_compiled_program()
+        .expect("Failed to compile program")
+        .script;
+    let main_module = VerifiedScript::new(script).unwrap().into_module();
+    let loaded_main = LoadedModule::new(main_module);
+
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new(&allocator);
+    let data_cache = FakeDataCache::new();
+
+    for (x, expected_abort) in vec![(5, None), (6, Some(42))] {
+        let run_with_args = {
+            let entry_func = FunctionRef::new(&loaded_main, CompiledScript::MAIN_INDEX);
+            let mut vm = TransactionExecutor::new(
+                &module_cache,
+                &data_cache,
+                TransactionMetadata::default(),
+            );
+            let args = vec![
+                TransactionArgument::U64(x),
+                TransactionArgument::ByteArray(ByteArray::new(vec![1, 2])),
+            ];
+            vm.execute_main(entry_func, args).unwrap()
+        };
+        let run_with_values = {
+            let entry_func = FunctionRef::new(&loaded_main, CompiledScript::MAIN_INDEX);
+            let mut vm = TransactionExecutor::new(
+                &module_cache,
+                &data_cache,
+                TransactionMetadata::default(),
+            );
+            let args = vec![Local::u64(x), Local::bytearray(ByteArray::new(vec![1, 2]))];
+            vm.execute_main_with_values(entry_func, args).unwrap()
+        };
+
+        for result in vec![run_with_args, run_with_values] {
+            match (result, expected_abort) {
+                (Ok(()), None) => (),
+                (Err(VMRuntimeError { err, .. }), Some(code)) => {
+                    assert_eq!(err, VMErrorKind::Aborted(code))
+                }
+                (result, _) => panic!("unexpected result {:?} for x = {}", result, x),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_breakpoint() {
+    let allocator = Arena::new();
+    let (module_id, module_cache) = setup_module(INCR_MODULE, &allocator);
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+
+    let mut breakpoints = HashSet::new();
+    breakpoints.insert((module_id.clone(), "incr".to_string(), 2));
+    vm.set_breakpoints(breakpoints);
+
+    // incr is MoveLoc(0), LdConst(1), Add, ...: the break is right before the addition.
+    match vm
+        .debug_function(&module_id, "incr", vec![Local::u64(5)])
+        .unwrap()
+        .unwrap()
+    {
+        ExecutionOutcome::DebugBreak(state) => {
+            assert_eq!(state.module, module_id);
+            assert_eq!(state.function_name, "incr");
+            assert_eq!(state.pc, 2);
+            assert_eq!(state.stack.len(), 2);
+            assert!(state.stack[0].clone().equals(Local::u64(5)).unwrap());
+            assert!(state.stack[1].clone().equals(Local::u64(1)).unwrap());
+        }
+        ExecutionOutcome::Completed => panic!("expected to pause at the breakpoint"),
+    }
+
+    match vm.continue_execution().unwrap().unwrap() {
+        ExecutionOutcome::Completed => {}
+        ExecutionOutcome::DebugBreak(state) => panic!("unexpected break at {}", state.pc),
+    }
+    assert!(vm.pop_stack().unwrap().equals(Local::u64(6)).unwrap());
+}
+
+#[test]
+fn test_breakpoint_ignored_outside_debugger() {
+    let allocator = Arena::new();
+    let (module_id, module_cache) = setup_module(INCR_MODULE, &allocator);
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+
+    let mut breakpoints = HashSet::new();
+    breakpoints.insert((module_id.clone(), "incr".to_string(), 2));
+    vm.set_breakpoints(breakpoints);
+    match vm
+        .debug_function(&module_id, "incr", vec![Local::u64(5)])
+        .unwrap()
+        .unwrap()
+    {
+        ExecutionOutcome::DebugBreak(state) => assert_eq!(state.pc, 2),
+        ExecutionOutcome::Completed => panic!("expected to pause at the breakpoint"),
+    }
+
+    // While the break is left pending, running the same function normally goes straight past the
+    // breakpoint.
+    vm.execute_function(&module_id, "incr", vec![Local::u64(7)])
+        .unwrap()
+        .unwrap();
+    assert!(vm.pop_stack().unwrap().equals(Local::u64(8)).unwrap());
+
+    // The paused function can still be resumed afterwards.
+    match vm.continue_execution().unwrap().unwrap() {
+        ExecutionOutcome::Completed => {}
+        ExecutionOutcome::DebugBreak(state) => panic!("unexpected break at {}", state.pc),
+    }
+    assert!(vm.pop_stack().unwrap().equals(Local::u64(6)).unwrap());
+}
+
+#[test]
+fn test_in_memory_trace() {
+    let allocator = Arena::new();
+    let (module_id, module_cache) = setup_module(INCR_MODULE, &allocator);
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+
+    vm.enable_in_memory_trace();
+    vm.execute_function(&module_id, "incr", vec![Local::u64(5)])
+        .unwrap()
+        .unwrap();
+
+    let function_path = format!("{}::M::incr", module_id.address());
+    let expected = vec![
+        Bytecode::MoveLoc(0),
+        Bytecode::LdConst(1),
+        Bytecode::Add,
+        Bytecode::StLoc(1),
+        Bytecode::MoveLoc(1),
+        Bytecode::Ret,
+    ];
+    let trace = vm.take_trace();
+    assert_eq!(trace.len(), expected.len());
+    for (pc, ((path, offset, instr), expected_instr)) in trace.into_iter().zip(expected).enumerate()
+    {
+        assert_eq!(path, function_path);
+        assert_eq!(offset as usize, pc);
+        assert_eq!(instr, expected_instr);
+    }
+    assert!(vm.take_trace().is_empty());
+}
+
+#[test]
+fn test_hidden_txn_public_key() {
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new(&allocator);
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(module_cache, &data_cache, TransactionMetadata::default());
+    vm.set_expose_txn_public_key(false);
+
+    vm.execution_stack.push_frame(fake_script_entry(&allocator));
+
+    match test_simple_instruction_impl(
+        &mut vm,
+        Bytecode::GetTxnPublicKey,
+        vec![],
+        vec![],
+        vec![],
+        vec![],
+        1,
+    )
+    .unwrap()
+    {
+        Err(VMRuntimeError { err, .. }) => assert_eq!(err, VMErrorKind::TxnFieldUnavailable),
+        Ok(()) => panic!("reading the public key should fail when it is not exposed"),
+    }
+    assert!(vm.execution_stack.get_value_stack().is_empty());
+}
+
+#[test]
+fn test_read_local() {
+    let allocator = Arena::new();
+    let (module_id, module_cache) = setup_module(INCR_MODULE, &allocator);
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+
+    // Pause at `MoveLoc(1)`, once `y` has been stored.
+    let mut breakpoints = HashSet::new();
+    breakpoints.insert((module_id.clone(), "incr".to_string(), 4));
+    vm.set_breakpoints(breakpoints);
+    match vm
+        .debug_function(&module_id, "incr", vec![Local::u64(5)])
+        .unwrap()
+        .unwrap()
+    {
+        ExecutionOutcome::DebugBreak(state) => assert_eq!(state.pc, 4),
+        ExecutionOutcome::Completed => panic!("expected to pause at the breakpoint"),
+    }
+
+    assert!(vm.read_local(0, 1).unwrap().equals(Local::u64(6)).unwrap());
+    // `x` was moved out of.
+    assert_matches!(vm.read_local(0, 0).unwrap(), Local::Invalid);
+    // Reading doesn't move the local.
+    assert!(vm.read_local(0, 1).unwrap().equals(Local::u64(6)).unwrap());
+    assert_eq!(
+        vm.read_local(1, 0).unwrap_err(),
+        VMInvariantViolation::EmptyCallStack
+    );
+    assert_matches!(
+        vm.read_local(0, 2).unwrap_err(),
+        VMInvariantViolation::IndexOutOfBounds(_, 2, 2)
+    );
+}
+
+#[test]
+fn test_debug_print() {
+    let code = format!(
+        "
+        module Debug {{
+            native public print(x: u64);
+        }}
+        module M {{
+            import 0x{0}.Debug;
+            public f() {{
+                Debug.print(42);
+                return;
+            }}
+        }}
+        ",
+        hex::encode(AccountAddress::default())
+    );
+    let modules = compile_modules(&code);
+    let module_id = modules[1].self_id();
+
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new(&allocator);
+    for module in modules {
+        module_cache.cache_module(VerifiedModule::new(module).unwrap());
+    }
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+
+    vm.enable_debug_output();
+    vm.execute_function(&module_id, "f", vec![])
+        .unwrap()
+        .unwrap();
+    assert_eq!(vm.take_debug_output(), vec!["42".to_string()]);
+    assert!(vm.take_debug_output().is_empty());
+    assert!(vm.pending_writes().unwrap().is_empty());
+    assert!(vm.execution_stack.get_value_stack().is_empty());
+}
+
+#[test]
+fn test_copy_loc_gas_scales_with_value_size() {
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new(&allocator);
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+    vm.execution_stack.push_frame(fake_script_entry(&allocator));
+
+    let small = Local::u64(1);
+    let large = Local::struct_((0..16).map(|i| MutVal::new(Value::U64(i))).collect());
+    let small_size = small.size();
+    let large_size = large.size();
+    vm.execution_stack
+        .top_frame_mut()
+        .unwrap()
+        .set_with_states(0, vec![small, large]);
+
+    let small_gas = vm
+        .gas_meter
+        .gas_for_instruction(
+            &Bytecode::CopyLoc(0),
+            &vm.execution_stack,
+            AbstractMemorySize::new(1),
+        )
+        .unwrap()
+        .unwrap();
+    let large_gas = vm
+        .gas_meter
+        .gas_for_instruction(
+            &Bytecode::CopyLoc(1),
+            &vm.execution_stack,
+            AbstractMemorySize::new(1),
+        )
+        .unwrap()
+        .unwrap();
+
+    // Copying is charged in proportion to the size of the copied value.
+    assert!(large_gas.get() > small_gas.get());
+    assert_eq!(
+        large_gas.get() * small_size.get(),
+        small_gas.get() * large_size.get()
+    );
+}
+
+#[test]
+fn test_instruction_budget() {
+    let code = "
+        module M {
+            public spin() {
+                let i: u64;
+                i = 0;
+                loop {
+                    i = move(i) + 1;
+                }
+            }
+        }
+        ";
+    let allocator = Arena::new();
+    let (module_id, module_cache) = setup_module(code, &allocator);
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+
+    // Without gas the loop would never end; the budget stops it.
+    vm.gas_meter.disable_metering();
+    vm.set_instruction_budget(Some(100));
+    match vm.execute_function(&module_id, "spin", vec![]).unwrap() {
+        Err(VMRuntimeError { err, .. }) => assert_eq!(err, VMErrorKind::ExecutionLimitReached),
+        Ok(()) => panic!("spin should not terminate"),
+    }
+}
+
+#[test]
+fn test_gas_by_function() {
+    let code = "
+        module M {
+            public helper(x: u64): u64 {
+                return move(x) + 1;
+            }
+            public run() {
+                let i: u64;
+                i = 0;
+                while (copy(i) < 5) {
+                    i = Self.helper(move(i));
+                }
+                return;
+            }
+        }
+        ";
+    let allocator = Arena::new();
+    let (module_id, module_cache) = setup_module(code, &allocator);
+    let helper = (module_id.clone(), "helper".to_string());
+    let run = (module_id.clone(), "run".to_string());
+    let data_cache = FakeDataCache::new();
+
+    // Attribution is off by default.
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+    vm.execute_function(&module_id, "run", vec![])
+        .unwrap()
+        .unwrap();
+    assert!(vm.take_gas_by_function().is_empty());
+
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+    vm.enable_gas_by_function();
+    vm.execute_function(&module_id, "helper", vec![Local::u64(0)])
+        .unwrap()
+        .unwrap();
+    let gas_per_call = vm.take_gas_by_function()[&helper];
+    assert!(gas_per_call > 0);
+
+    vm.pop_stack().unwrap();
+    vm.execute_function(&module_id, "run", vec![])
+        .unwrap()
+        .unwrap();
+    let gas_by_function = vm.take_gas_by_function();
+    assert_eq!(gas_by_function.len(), 2);
+    assert_eq!(gas_by_function[&helper], 5 * gas_per_call);
+    // The caller's gas includes that of its callee.
+    assert!(gas_by_function[&run] > gas_by_function[&helper]);
+}
+
+#[test]
+fn test_execute_sandboxed_collects_aborts() {
+    let code = "
+        module M {
+            public first(x: u64) {
+                abort move(x);
+            }
+            public second() {
+                abort 2;
+            }
+        }
+        ";
+    let allocator = Arena::new();
+    let (module_id, module_cache) = setup_module(code, &allocator);
+    let path = |name: &str| format!("{}::{}::{}", module_id.address(), module_id.name(), name);
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+
+    let mut aborts = vec![];
+    vm.execute_sandboxed(&module_id, "first", vec![Local::u64(1)], Some(&mut aborts))
+        .unwrap()
+        .unwrap();
+    vm.execute_sandboxed(&module_id, "second", vec![], Some(&mut aborts))
+        .unwrap()
+        .unwrap();
+    assert_eq!(aborts, vec![(path("first"), 1), (path("second"), 2)]);
+    assert!(vm.execution_stack.is_call_stack_empty());
+    assert!(vm.execution_stack.get_value_stack().is_empty());
+
+    // Without a collection, the abort is reported as usual.
+    match vm
+        .execute_sandboxed(&module_id, "second", vec![], None)
+        .unwrap()
+    {
+        Err(VMRuntimeError { err, .. }) => assert_eq!(err, VMErrorKind::Aborted(2)),
+        Ok(()) => panic!("second should abort"),
+    }
+    assert!(vm.execution_stack.is_call_stack_empty());
+}
+
+#[test]
+fn test_execute_sandboxed_gas() {
+    let code = "
+        module M {
+            public sum(x: u64, y: u64): u64 {
+                return move(x) + move(y);
+            }
+        }
+        ";
+    let allocator = Arena::new();
+    let (module_id, module_cache) = setup_module(code, &allocator);
+    let data_cache = FakeDataCache::new();
+    let args = || vec![Local::u64(1), Local::u64(2)];
+
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+    let gas_left = vm.gas_meter.remaining_gas();
+    vm.execute_function(&module_id, "sum", args())
+        .unwrap()
+        .unwrap();
+    let function_gas = gas_left
+        .sub(vm.gas_meter.remaining_gas())
+        .sub(vm.gas_meter.intrinsic_gas_charged());
+    assert!(function_gas.get() > 0);
+
+    // Only the function itself is charged for, not the transaction.
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+    vm.execute_sandboxed(&module_id, "sum", args(), None)
+        .unwrap()
+        .unwrap();
+    assert_eq!(gas_left.sub(vm.gas_meter.remaining_gas()), function_gas);
+    assert_eq!(vm.gas_meter.intrinsic_gas_charged(), GasUnits::new(0));
+}
+
+#[test]
+fn test_strict_resource_checks() {
+    let code = "
+        module M {
+            resource T { x: u64 }
+            public leak() {
+                let t: R#Self.T;
+                t = T { x: 1 };
+                return;
+            }
+            public make(): R#Self.T {
+                let t: R#Self.T;
+                t = T { x: 1 };
+                return move(t);
+            }
+        }
+        ";
+    let module = compile_modules(code).remove(0);
+    let module_id = module.self_id();
+
+    // `leak` drops a resource, so it has to bypass the verifier.
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new(&allocator);
+    module_cache.cache_module(VerifiedModule::bypass_verifier_DANGEROUS_FOR_TESTING_ONLY(
+        module,
+    ));
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+
+    // The check is off by default.
+    vm.execute_function(&module_id, "leak", vec![])
+        .unwrap()
+        .unwrap();
+
+    vm.set_strict_resource_checks(true);
+    match vm.execute_function(&module_id, "leak", vec![]).unwrap() {
+        Err(VMRuntimeError { err, .. }) => {
+            assert_eq!(err, VMErrorKind::UnsafeRetUnusedResources)
+        }
+        Ok(()) => panic!("leak should fail the resource check"),
+    }
+
+    // A resource that was moved out is fine.
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+    vm.set_strict_resource_checks(true);
+    vm.execute_function(&module_id, "make", vec![])
+        .unwrap()
+        .unwrap();
+}
+
+#[test]
+fn test_out_of_bounds_pool_index() {
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new(&allocator);
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+    vm.execution_stack.push_frame(fake_script_entry(&allocator));
+
+    // The script has a single entry in each pool, so index 1 is out of bounds. Such code can only
+    // be run by bypassing the bounds checker, e.g. during instruction synthesis.
+    for (instr, kind) in vec![
+        (
+            Bytecode::LdAddr(AddressPoolIndex::new(1)),
+            IndexKind::AddressPool,
+        ),
+        (
+            Bytecode::LdStr(StringPoolIndex::new(1)),
+            IndexKind::StringPool,
+        ),
+        (
+            Bytecode::LdByteArray(ByteArrayPoolIndex::new(1)),
+            IndexKind::ByteArrayPool,
+        ),
+    ] {
+        match vm.execute_block(&[instr], 0) {
+            Err(VMInvariantViolation::IndexOutOfBounds(err_kind, _, _)) => {
+                assert_eq!(err_kind, kind)
+            }
+            _ => panic!("expected an out of bounds index"),
+        }
+        assert!(vm.execution_stack.get_value_stack().is_empty());
+    }
+}
+
+#[test]
+fn test_gas_meter_reset() {
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new(&allocator);
+    let stack = ExecutionStack::new(&module_cache);
+    let mut gas_meter = GasMeter::new(GasUnits::new(1000));
+    gas_meter
+        .consume_gas(GasUnits::new(300), &stack)
+        .unwrap()
+        .unwrap();
+    gas_meter.disable_metering();
+
+    gas_meter.reset(GasUnits::new(500));
+    assert_eq!(gas_meter.remaining_gas(), GasUnits::new(500));
+    assert!(gas_meter.usage_by_category().is_empty());
+    // Metering is back on.
+    gas_meter
+        .consume_gas(GasUnits::new(200), &stack)
+        .unwrap()
+        .unwrap();
+    assert_eq!(gas_meter.remaining_gas(), GasUnits::new(300));
+}
+
+#[test]
+fn test_execute_function_with_budget() {
+    let code = "
+        module M {
+            public count(n: u64): u64 {
+                let i: u64;
+                i = 0;
+                while (copy(i) < copy(n)) {
+                    i = move(i) + 1;
+                }
+                return move(i);
+            }
+        }
+        ";
+    let allocator = Arena::new();
+    let (module_id, module_cache) = setup_module(code, &allocator);
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+    let gas_left = vm.gas_meter.remaining_gas();
+
+    let gas_used = vm
+        .execute_function_with_budget(
+            &module_id,
+            "count",
+            vec![Local::u64(3)],
+            GasUnits::new(1_000_000),
+        )
+        .unwrap()
+        .unwrap();
+    assert!(gas_used.get() > 0);
+    assert!(vm.pop_stack().unwrap().equals(Local::u64(3)).unwrap());
+    assert_eq!(vm.gas_meter.remaining_gas(), gas_left);
+
+    // Running out of gas leaves the stacks as they were before the call.
+    vm.execution_stack.push(Local::u64(9));
+    match vm
+        .execute_function_with_budget(&module_id, "count", vec![Local::u64(3)], GasUnits::new(5))
+        .unwrap()
+    {
+        Err(VMRuntimeError { err, .. }) => assert_eq!(err, VMErrorKind::OutOfGasError),
+        Ok(_) => panic!("count should run out of gas"),
+    }
+    assert_eq!(vm.gas_meter.remaining_gas(), gas_left);
+    assert!(vm.execution_stack.is_call_stack_empty());
+    assert_eq!(vm.execution_stack.get_value_stack().len(), 1);
+    assert!(vm.pop_stack().unwrap().equals(Local::u64(9)).unwrap());
+}
+
+struct CountingFetcher {
+    fetcher: FakeFetcher,
+    fetches: Rc<Cell<usize>>,
+}
+
+impl ModuleFetcher for CountingFetcher {
+    fn get_module(&self, key: &ModuleId) -> Option<CompiledModule> {
+        self.fetches.set(self.fetches.get() + 1);
+        self.fetcher.get_module(key)
+    }
+}
+
+#[test]
+fn test_preload_modules() {
+    let code = "
+        module M {
+            public f() {
+                return;
+            }
+        }
+        ";
+    let module = compile_modules(code).remove(0);
+    let module_id = module.self_id();
+
+    let allocator = Arena::new();
+    let vm_cache = VMModuleCache::new(&allocator);
+    let fetches = Rc::new(Cell::new(0));
+    let fetcher = CountingFetcher {
+        fetcher: FakeFetcher::new(vec![module]),
+        fetches: fetches.clone(),
+    };
+    let block_cache = BlockModuleCache::new(&vm_cache, fetcher);
+    let data_cache = FakeDataCache::new();
+    let mut vm = TransactionExecutor::new(block_cache, &data_cache, TransactionMetadata::default());
+
+    vm.preload_modules(&[module_id.clone()]).unwrap().unwrap();
+    assert_eq!(fetches.get(), 1);
+    vm.execute_function(&module_id, "f", vec![])
+        .unwrap()
+        .unwrap();
+    assert_eq!(fetches.get(), 1);
+
+    let missing = ModuleId::new(AccountAddress::default(), "N".to_string());
+    assert_eq!(
+        vm.preload_modules(&[missing]).unwrap_err(),
+        VMInvariantViolation::LinkerError
+    );
+}
+
+#[test]
+fn test_end_of_code_is_return() {
+    let allocator = Arena::new();
+    let module_cache = VMModuleCache::new(&allocator);
+    let data_cache = FakeDataCache::new();
+    let mut vm =
+        TransactionExecutor::new(&module_cache, &data_cache, TransactionMetadata::default());
+    vm.execution_stack.push_frame(fake_script_entry(&allocator));
+    let code = [Bytecode::LdTrue, Bytecode::Pop];
+
+    // By default, as in production, running off the end of the code is an invariant violation.
+    assert_eq!(
+        vm.execute_block(&code, 0).unwrap_err(),
+        VMInvariantViolation::ProgramCounterOverflow
+    );
+
+    vm.set_end_of_code_is_return(true);
+    assert_eq!(vm.execute_block(&code, 0).unwrap().unwrap(), 2);
+    assert!(vm.execution_stack.get_value_stack().is_empty());
 }
@@ -190,3 +190,18 @@ fn test_simple_global_ref_err() {
     // error on another ReleaseRef
     assert!(root.release_reference().is_err());
 }
+
+#[test]
+fn test_display() {
+    let v = Local::struct_(vec![
+        MutVal::u64(1),
+        MutVal::bool(true),
+        MutVal::struct_(vec![MutVal::string("hi".to_string())]),
+    ]);
+    assert_eq!(v.to_string(), "{1, true, {\"hi\"}}");
+    assert_eq!(
+        v.borrow_local().unwrap().to_string(),
+        "&{1, true, {\"hi\"}}"
+    );
+    assert_eq!(Local::Invalid.to_string(), "<invalid>");
+}
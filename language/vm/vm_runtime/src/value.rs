@@ -4,6 +4,7 @@
 use crate::loaded_data::{struct_def::StructDef, types::Type};
 use std::{
     cell::{Ref, RefCell},
+    fmt,
     ops::Add,
     rc::Rc,
 };
@@ -499,6 +500,11 @@ impl GlobalRef {
         }
     }
 
+    // Serialize the resource behind the reference without giving up ownership of it.
+    pub fn simple_serialize(&self) -> Option<Vec<u8>> {
+        self.reference.peek().simple_serialize()
+    }
+
     pub fn is_loadable(&self) -> bool {
         self.root.borrow().ref_count == 0 && !self.is_deleted()
     }
@@ -592,6 +598,41 @@ impl Reference for GlobalRef {
 // Conversion routines for the interpreter
 //
 
+/// Prints a value the way it would be written by hand, e.g. `{1, true}` for a struct holding a
+/// `u64` and a `bool`.
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Address(addr) => write!(f, "{}", addr),
+            Value::U64(i) => write!(f, "{}", i),
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::String(s) => write!(f, "{:?}", s),
+            Value::ByteArray(bytes) => write!(f, "{}", bytes),
+            Value::Struct(fields) => {
+                write!(f, "{{")?;
+                for (idx, field) in fields.iter().enumerate() {
+                    if idx > 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{}", &*field.peek())?;
+                }
+                write!(f, "}}")
+            }
+        }
+    }
+}
+
+impl fmt::Display for Local {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Local::Ref(v) => write!(f, "&{}", &*v.peek()),
+            Local::GlobalRef(v) => write!(f, "&{}", &*v.reference.peek()),
+            Local::Value(v) => write!(f, "{}", &*v.peek()),
+            Local::Invalid => write!(f, "<invalid>"),
+        }
+    }
+}
+
 impl From<MutVal> for Option<u64> {
     fn from(value: MutVal) -> Option<u64> {
         match &*value.peek() {
@@ -134,6 +134,8 @@ pub enum VMInvariantViolationError {
     LocalReferenceError,
     StorageError,
     InternalTypeError,
+    StackImbalance,
+    GasScheduleError,
 }
 
 #[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
@@ -168,7 +170,16 @@ pub enum ArithmeticErrorType {
     DivisionByZero,
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug, Hash)]
+/// Where a Move program aborted: the name of the function and the offset of the aborting
+/// instruction in that function.
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
+#[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
+pub struct AbortLocation {
+    pub function: String,
+    pub code_offset: u16,
+}
+
+#[derive(Clone, PartialEq, Eq, Debug, Hash)]
 #[cfg_attr(any(test, feature = "testing"), derive(Arbitrary))]
 pub enum ExecutionStatus {
     Executed,
@@ -185,10 +196,15 @@ pub enum ExecutionStatus {
     CannotWriteExistingResource,
     ValueSerializationError,
     ValueDeserializationError,
-    Aborted(u64),
+    /// A Move abort, with its code and, when known, where it happened.
+    Aborted(u64, Option<AbortLocation>),
     ArithmeticError(ArithmeticErrorType),
     DynamicReferenceError(DynamicReferenceErrorType),
     DuplicateModuleName,
+    ValueDepthExceeded,
+    TxnFieldUnavailable,
+    ExecutionLimitReached,
+    UnsafeRetUnusedResources,
 }
 
 #[derive(Clone, PartialEq, Eq, Debug, Hash)]
@@ -241,6 +257,8 @@ pub enum DecodingError {
     UnknownRuntimeStatusEncountered,
     #[fail(display = "Unknown/Invalid VM Status Encountered")]
     InvalidVMStatusEncountered,
+    #[fail(display = "Code offset {} greater than max possible value 65535", _0)]
+    CodeOffsetTooBig(u32),
 }
 
 //***********************************
@@ -816,6 +834,8 @@ impl IntoProto for VMInvariantViolationError {
             VMInvariantViolationError::LocalReferenceError => ProtoStatus::LocalReferenceError,
             VMInvariantViolationError::StorageError => ProtoStatus::StorageError,
             VMInvariantViolationError::InternalTypeError => ProtoStatus::InternalTypeError,
+            VMInvariantViolationError::StackImbalance => ProtoStatus::StackImbalance,
+            VMInvariantViolationError::GasScheduleError => ProtoStatus::GasScheduleError,
         }
     }
 }
@@ -835,6 +855,8 @@ impl FromProto for VMInvariantViolationError {
             ProtoError::LocalReferenceError => Ok(VMInvariantViolationError::LocalReferenceError),
             ProtoError::StorageError => Ok(VMInvariantViolationError::StorageError),
             ProtoError::InternalTypeError => Ok(VMInvariantViolationError::InternalTypeError),
+            ProtoError::StackImbalance => Ok(VMInvariantViolationError::StackImbalance),
+            ProtoError::GasScheduleError => Ok(VMInvariantViolationError::GasScheduleError),
             ProtoError::UnknownInvariantViolationError => {
                 bail_err!(DecodingError::UnknownInvariantViolationErrorEncountered)
             }
@@ -958,8 +980,8 @@ impl IntoProto for ExecutionStatus {
 
     fn into_proto(self) -> Self::ProtoType {
         use crate::proto::vm_errors::{
-            Aborted, ArithmeticError, DynamicReferenceError, ExecutionStatus as ExecuteStatus,
-            RuntimeStatus,
+            AbortLocation as ProtoAbortLocation, Aborted, ArithmeticError, DynamicReferenceError,
+            ExecutionStatus as ExecuteStatus, RuntimeStatus,
         };
         let mut exec_status = ExecuteStatus::new();
         match self {
@@ -1002,6 +1024,18 @@ impl IntoProto for ExecutionStatus {
             ExecutionStatus::DuplicateModuleName => {
                 exec_status.set_runtime_status(RuntimeStatus::DuplicateModuleName)
             }
+            ExecutionStatus::ValueDepthExceeded => {
+                exec_status.set_runtime_status(RuntimeStatus::ValueDepthExceeded)
+            }
+            ExecutionStatus::TxnFieldUnavailable => {
+                exec_status.set_runtime_status(RuntimeStatus::TxnFieldUnavailable)
+            }
+            ExecutionStatus::ExecutionLimitReached => {
+                exec_status.set_runtime_status(RuntimeStatus::ExecutionLimitReached)
+            }
+            ExecutionStatus::UnsafeRetUnusedResources => {
+                exec_status.set_runtime_status(RuntimeStatus::UnsafeRetUnusedResources)
+            }
             ExecutionStatus::DynamicReferenceError(err_type) => {
                 let mut ref_err = DynamicReferenceError::new();
                 let err_code = DynamicReferenceErrorType::into_proto(err_type);
@@ -1014,9 +1048,15 @@ impl IntoProto for ExecutionStatus {
                 arith_err.set_error_code(err_code);
                 exec_status.set_arithmetic_error(arith_err)
             }
-            ExecutionStatus::Aborted(err_code) => {
+            ExecutionStatus::Aborted(err_code, location) => {
                 let mut aborted = Aborted::new();
                 aborted.set_aborted_error_code(err_code);
+                if let Some(location) = location {
+                    let mut proto_location = ProtoAbortLocation::new();
+                    proto_location.set_function(location.function);
+                    proto_location.set_code_offset(u32::from(location.code_offset));
+                    aborted.set_location(proto_location);
+                }
                 exec_status.set_aborted(aborted)
             }
         };
@@ -1060,6 +1100,14 @@ impl FromProto for ExecutionStatus {
                     Ok(ExecutionStatus::ValueDeserializationError)
                 }
                 ProtoRuntimeStatus::DuplicateModuleName => Ok(ExecutionStatus::DuplicateModuleName),
+                ProtoRuntimeStatus::ValueDepthExceeded => Ok(ExecutionStatus::ValueDepthExceeded),
+                ProtoRuntimeStatus::TxnFieldUnavailable => Ok(ExecutionStatus::TxnFieldUnavailable),
+                ProtoRuntimeStatus::ExecutionLimitReached => {
+                    Ok(ExecutionStatus::ExecutionLimitReached)
+                }
+                ProtoRuntimeStatus::UnsafeRetUnusedResources => {
+                    Ok(ExecutionStatus::UnsafeRetUnusedResources)
+                }
                 ProtoRuntimeStatus::UnknownRuntimeStatus => {
                     bail_err!(DecodingError::UnknownRuntimeStatusEncountered)
                 }
@@ -1078,8 +1126,24 @@ impl FromProto for ExecutionStatus {
             Ok(ExecutionStatus::DynamicReferenceError(from_proto))
         } else {
             // else it's an assertion error
-            let err_code = proto_execution_status.get_aborted();
-            Ok(ExecutionStatus::Aborted(err_code.aborted_error_code))
+            let mut aborted = proto_execution_status.take_aborted();
+            let location = if aborted.has_location() {
+                let mut proto_location = aborted.take_location();
+                let code_offset = proto_location.get_code_offset();
+                if code_offset > u32::from(u16::max_value()) {
+                    bail_err!(DecodingError::CodeOffsetTooBig(code_offset));
+                }
+                Some(AbortLocation {
+                    function: proto_location.take_function(),
+                    code_offset: code_offset as u16,
+                })
+            } else {
+                None
+            };
+            Ok(ExecutionStatus::Aborted(
+                aborted.aborted_error_code,
+                location,
+            ))
         }
     }
 }